@@ -0,0 +1,1597 @@
+//! Contains the `Corpus` struct and methods to analyze a layout with it, like counting same finger
+//! bigrams.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+    dofinitions::{Finger, Hand, Key, SpecialKey},
+    interaction::Pos,
+    keyboard::{Geometry, PhysicalKeyboard},
+    Dof, DofErrorInner as DE, DofIntermediate, Fingering, Keyboard, Layer, ParsedFingering, Result,
+};
+
+/// Frequency model used to analyze layouts with, containing counts of unigrams, bigrams and trigrams.
+/// Each n-gram is stored as a string of n characters.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Corpus {
+    unigrams: BTreeMap<String, u64>,
+    bigrams: BTreeMap<String, u64>,
+    trigrams: BTreeMap<String, u64>,
+}
+
+impl Corpus {
+    /// Create a new corpus from existing unigram, bigram and trigram counts.
+    pub fn new(
+        unigrams: BTreeMap<String, u64>,
+        bigrams: BTreeMap<String, u64>,
+        trigrams: BTreeMap<String, u64>,
+    ) -> Self {
+        Self {
+            unigrams,
+            bigrams,
+            trigrams,
+        }
+    }
+
+    /// Count all unigrams, bigrams and trigrams in a piece of text.
+    pub fn from_text(text: &str) -> Self {
+        let chars = text.chars().collect::<Vec<_>>();
+
+        let count = |n: usize| {
+            chars.windows(n).fold(BTreeMap::new(), |mut acc, w| {
+                *acc.entry(w.iter().collect::<String>()).or_insert(0) += 1;
+                acc
+            })
+        };
+
+        Self {
+            unigrams: count(1),
+            bigrams: count(2),
+            trigrams: count(3),
+        }
+    }
+
+    /// Merge multiple corpora into one, multiplying each of their counts by the provided weight. As the
+    /// weights are applied to raw counts, corpora of very different sizes should have their weights
+    /// adjusted accordingly. Merged counts are rounded to the nearest integer.
+    pub fn weighted_merge(corpora: &[(Corpus, f64)]) -> Corpus {
+        let merge = |get: fn(&Corpus) -> &BTreeMap<String, u64>| {
+            let weighted = corpora
+                .iter()
+                .fold(BTreeMap::new(), |mut acc, (corpus, weight)| {
+                    for (ngram, &count) in get(corpus) {
+                        *acc.entry(ngram.clone()).or_insert(0.0) += count as f64 * weight;
+                    }
+                    acc
+                });
+
+            weighted
+                .into_iter()
+                .map(|(ngram, count)| (ngram, count.round() as u64))
+                .filter(|(_, count)| *count > 0)
+                .collect()
+        };
+
+        Self {
+            unigrams: merge(Corpus::unigrams),
+            bigrams: merge(Corpus::bigrams),
+            trigrams: merge(Corpus::trigrams),
+        }
+    }
+
+    /// Get the unigram counts of the corpus.
+    pub fn unigrams(&self) -> &BTreeMap<String, u64> {
+        &self.unigrams
+    }
+
+    /// Get the bigram counts of the corpus.
+    pub fn bigrams(&self) -> &BTreeMap<String, u64> {
+        &self.bigrams
+    }
+
+    /// Get the trigram counts of the corpus.
+    pub fn trigrams(&self) -> &BTreeMap<String, u64> {
+        &self.trigrams
+    }
+}
+
+/// Grid of how much effort it takes to press each position of a layer, where a higher value means a
+/// position is harder to press. Used to weigh key presses in
+/// [`weighted_finger_load()`](crate::DofIntermediate::weighted_finger_load).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Effort(Vec<Vec<f64>>);
+
+impl From<Vec<Vec<f64>>> for Effort {
+    fn from(grid: Vec<Vec<f64>>) -> Self {
+        Self(grid)
+    }
+}
+
+impl Keyboard for Effort {
+    type K = f64;
+
+    fn inner(&self) -> &[Vec<Self::K>] {
+        &self.0
+    }
+
+    fn into_inner(self) -> Vec<Vec<Self::K>> {
+        self.0
+    }
+}
+
+/// Amount of values every position on the board contributes to
+/// [`feature_matrix()`](crate::DofIntermediate::feature_matrix).
+pub const FEATURES_PER_KEY: usize = 7;
+
+/// Direction of a one-handed roll between two fingers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RollKind {
+    /// Rolling from the pinky towards the index finger.
+    Inward,
+    /// Rolling from the index finger towards the pinky.
+    Outward,
+}
+
+/// Analysis results of a layer with a single fingering, used in a
+/// [`FingeringComparison`](crate::analysis::FingeringComparison).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FingeringStats {
+    /// Weighted amount of same finger bigrams.
+    pub sfbs: u64,
+    /// Weighted amount of bigrams that are rolls, in either direction.
+    pub rolls: u64,
+    /// Weighted amount of bigrams that alternate hands.
+    pub alternation: u64,
+}
+
+/// Comparison of the same layer typed with two different fingerings, as returned by
+/// [`compare_fingerings()`](crate::DofIntermediate::compare_fingerings).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FingeringComparison {
+    /// Stats of the first fingering.
+    pub a: FingeringStats,
+    /// Stats of the second fingering.
+    pub b: FingeringStats,
+}
+
+impl FingeringComparison {
+    /// Change in same finger bigrams going from the first fingering to the second.
+    pub fn sfb_delta(&self) -> i64 {
+        self.b.sfbs as i64 - self.a.sfbs as i64
+    }
+
+    /// Change in rolls going from the first fingering to the second.
+    pub fn roll_delta(&self) -> i64 {
+        self.b.rolls as i64 - self.a.rolls as i64
+    }
+
+    /// Change in alternation going from the first fingering to the second.
+    pub fn alternation_delta(&self) -> i64 {
+        self.b.alternation as i64 - self.a.alternation as i64
+    }
+}
+
+impl DofIntermediate {
+    /// Map every character on a layer to its position and the finger it is pressed with. If a character
+    /// occurs more than once, the first occurrence is used. Keys pressed with `Finger::Unassigned` can't be
+    /// typed and are left out.
+    pub(crate) fn char_fingers(&self, layer: &str) -> Result<BTreeMap<char, (Pos, Finger)>> {
+        let fingering = self.explicit_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        Ok(char_index(layer, &fingering))
+    }
+
+    /// Like [`char_fingers()`](crate::DofIntermediate::char_fingers), but with a provided fingering
+    /// instead of the layout's own. Returns an `IncompatibleFingeringShape` error if the fingering doesn't
+    /// have the same shape as the layer.
+    fn char_fingers_with(
+        &self,
+        layer: &str,
+        fingering: &Fingering,
+    ) -> Result<BTreeMap<char, (Pos, Finger)>> {
+        let layer = self.layer_or_err(layer)?;
+
+        if fingering.shape() != layer.shape() {
+            return Err(DE::IncompatibleFingeringShape.into());
+        }
+
+        Ok(char_index(layer, fingering))
+    }
+
+    /// Count the weighted amount of bigrams on a layer that are typed with the same hand, regardless of
+    /// finger. Thumbs count as part of their hand. Bigrams containing characters that aren't on the layer
+    /// are ignored.
+    pub fn same_hand_bigrams(&self, layer: &str, bigrams: &[(String, u64)]) -> Result<u64> {
+        let index = self.char_fingers(layer)?;
+
+        let count = bigrams
+            .iter()
+            .filter_map(|(bigram, count)| {
+                let mut chars = bigram.chars();
+                let (_, f1) = index.get(&chars.next()?)?;
+                let (_, f2) = index.get(&chars.next()?)?;
+
+                (f1.hand() == f2.hand()).then_some(count)
+            })
+            .sum();
+
+        Ok(count)
+    }
+
+    /// Check if two characters on a layer form a roll, being typed by two different fingers on the same
+    /// hand, and if so in which direction. Thumbs never take part in rolls. Returns `None` if either
+    /// character isn't on the layer.
+    pub fn is_roll(&self, layer: &str, a: char, b: char) -> Result<Option<RollKind>> {
+        let index = self.char_fingers(layer)?;
+
+        let (Some((_, f1)), Some((_, f2))) = (index.get(&a), index.get(&b)) else {
+            return Ok(None);
+        };
+
+        Ok(roll_kind(*f1, *f2))
+    }
+
+    /// Get the fraction of key presses done by the left and right hand when typing a set of weighted
+    /// unigrams on a layer, as `(left, right)`. Thumbs count as part of their hand. Characters that aren't
+    /// on the layer are ignored, and if none of the characters are on it, both fractions are 0.0.
+    pub fn hand_usage(&self, layer: &str, unigrams: &[(char, u64)]) -> Result<(f64, f64)> {
+        let index = self.char_fingers(layer)?;
+
+        let (left, right) = unigrams
+            .iter()
+            .filter_map(|(c, count)| index.get(c).map(|(_, f)| (f, *count)))
+            .fold((0u64, 0u64), |(left, right), (f, count)| match f.hand() {
+                Some(Hand::Left) => (left + count, right),
+                Some(Hand::Right) => (left, right + count),
+                None => (left, right),
+            });
+
+        match left + right {
+            0 => Ok((0.0, 0.0)),
+            total => Ok((left as f64 / total as f64, right as f64 / total as f64)),
+        }
+    }
+
+    /// Count the keys on a layer pressed by the left hand, either thumb and the right hand, as
+    /// `(left, thumbs, right)`, regardless of which character they hold. Empty keys and keys without a
+    /// finger aren't counted. This is a coarser, but cheaper, measure of balance than
+    /// [`hand_usage()`](crate::DofIntermediate::hand_usage).
+    pub fn section_balance(&self, layer: &str) -> Result<(usize, usize, usize)> {
+        let fingering = self.explicit_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let balance = layer
+            .keys()
+            .zip(fingering.keys())
+            .filter(|(k, _)| !matches!(k, Key::Empty))
+            .fold((0, 0, 0), |(left, thumbs, right), (_, f)| {
+                match (f.is_thumb(), f.hand()) {
+                    (true, _) => (left, thumbs + 1, right),
+                    (_, Some(Hand::Left)) => (left + 1, thumbs, right),
+                    (_, Some(Hand::Right)) => (left, thumbs, right + 1),
+                    (_, None) => (left, thumbs, right),
+                }
+            });
+
+        Ok(balance)
+    }
+
+    /// Get the fraction of key presses done by each finger when typing a set of weighted unigrams on a
+    /// layer, indexed in the order of [`Finger::FINGERS`](crate::dofinitions::Finger::FINGERS). When
+    /// `include_thumbs` is set, characters on the thumb cluster are counted as well, unless they're also
+    /// on the layer. Characters that can't be typed are ignored, and if none can be, every fraction is 0.0.
+    /// Returns an `IncompatibleThumbFingeringShape` error if thumbs are included but the thumb cluster and
+    /// its fingering don't have the same shape.
+    pub fn finger_usage(
+        &self,
+        layer: &str,
+        unigrams: &[(char, u64)],
+        include_thumbs: bool,
+    ) -> Result<[f64; 10]> {
+        let mut index = self.char_fingers(layer)?;
+
+        if include_thumbs {
+            self.validate_thumbs()?;
+            add_thumbs(&mut index, &self.thumbs, &self.thumb_fingering);
+        }
+
+        Ok(count_finger_usage(&index, unigrams))
+    }
+
+    /// Get the load of each finger when typing a set of weighted unigrams on a layer, where every key press
+    /// adds its frequency multiplied by the effort of its position to the finger pressing it. Every finger
+    /// is included, even if it's never used. Characters that aren't on the layer are ignored. Returns an
+    /// `IncompatibleEffortShape` error if the effort grid doesn't have the same shape as the layer.
+    pub fn weighted_finger_load(
+        &self,
+        layer: &str,
+        unigrams: &[(char, u64)],
+        effort: &Effort,
+    ) -> Result<BTreeMap<Finger, f64>> {
+        let index = self.char_fingers(layer)?;
+
+        if effort.shape() != self.layer_or_err(layer)?.shape() {
+            return Err(DE::IncompatibleEffortShape.into());
+        }
+
+        let mut load = Finger::FINGERS
+            .into_iter()
+            .map(|f| (f, 0.0))
+            .collect::<BTreeMap<_, _>>();
+
+        for (c, count) in unigrams {
+            if let Some((pos, finger)) = index.get(c) {
+                let effort = effort.0[pos.row()][pos.col()];
+                *load.entry(*finger).or_insert(0.0) += *count as f64 * effort;
+            }
+        }
+
+        Ok(load)
+    }
+
+    /// Get a single number describing how unevenly the fingers are loaded when typing a set of weighted
+    /// unigrams on a layer, being the standard deviation of the load of each finger divided by the mean
+    /// load, as given by [`weighted_finger_load()`](crate::DofIntermediate::weighted_finger_load) where
+    /// every position takes the same effort. Thumbs are left out, as they usually only press space, which
+    /// isn't a character key. Lower is more balanced, and if no character can be typed this is 0.0.
+    pub fn finger_disbalance(&self, layer: &str, unigrams: &[(char, u64)]) -> Result<f64> {
+        let effort = self.uniform_effort(layer)?;
+
+        let loads = self
+            .weighted_finger_load(layer, unigrams, &effort)?
+            .into_iter()
+            .filter(|(f, _)| !f.is_thumb())
+            .map(|(_, load)| load)
+            .collect::<Vec<_>>();
+
+        let mean = loads.iter().sum::<f64>() / loads.len() as f64;
+
+        if mean == 0.0 {
+            return Ok(0.0);
+        }
+
+        let variance = loads.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / loads.len() as f64;
+
+        Ok(variance.sqrt() / mean)
+    }
+
+    /// Get the share of key presses each finger does when typing the unigrams of a corpus on a layer,
+    /// being [`weighted_finger_load()`](crate::DofIntermediate::weighted_finger_load) where every position
+    /// takes the same effort, divided by the total load so the shares sum to 1.0. Unigrams that aren't a
+    /// single character, or that aren't on the layer, are ignored. If none can be typed, every share is 0.0.
+    pub fn finger_load_fractions(
+        &self,
+        layer: &str,
+        unigrams: &Corpus,
+    ) -> Result<BTreeMap<Finger, f64>> {
+        let unigrams = unigrams
+            .unigrams()
+            .iter()
+            .filter_map(|(s, &count)| {
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Some((c, count)),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let effort = self.uniform_effort(layer)?;
+        let mut load = self.weighted_finger_load(layer, &unigrams, &effort)?;
+        let total = load.values().sum::<f64>();
+
+        if total > 0.0 {
+            load.values_mut().for_each(|l| *l /= total);
+        }
+
+        Ok(load)
+    }
+
+    fn uniform_effort(&self, layer: &str) -> Result<Effort> {
+        let effort = self
+            .layer_or_err(layer)?
+            .rows()
+            .map(|row| vec![1.0; row.len()])
+            .collect::<Vec<_>>();
+
+        Ok(effort.into())
+    }
+
+    /// Get a grid with the same shape as a layer where each cell is the frequency of the character at that
+    /// position, normalized so the most frequent position is 1.0. Positions without a character key, or
+    /// with a character that isn't in `unigrams`, are 0.0. If none of the characters are on the layer,
+    /// every cell is 0.0. Frequencies of characters that occur more than once in `unigrams` are summed.
+    pub fn heatmap(&self, layer: &str, unigrams: &[(char, u64)]) -> Result<Vec<Vec<f64>>> {
+        let layer = self.layer_or_err(layer)?;
+
+        let frequencies = unigrams
+            .iter()
+            .fold(HashMap::new(), |mut acc, &(c, count)| {
+                *acc.entry(c).or_insert(0u64) += count;
+                acc
+            });
+
+        let counts = layer
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|key| match key.tap() {
+                        Key::Char(c) => frequencies.get(c).copied().unwrap_or_default(),
+                        _ => 0,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let max = counts.iter().flatten().copied().max().unwrap_or_default();
+
+        let heatmap = counts
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|count| match max {
+                        0 => 0.0,
+                        max => count as f64 / max as f64,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(heatmap)
+    }
+
+    /// Count the weighted amount of scissors on a layer, being bigrams typed by adjacent fingers on the same
+    /// hand that are at least two rows apart. Thumbs are never considered adjacent to the index finger.
+    /// Bigrams containing characters that aren't on the layer are ignored.
+    pub fn scissor_count(&self, layer: &str, bigrams: &[(String, u64)]) -> Result<u64> {
+        let index = self.char_fingers(layer)?;
+
+        let count = bigrams
+            .iter()
+            .filter_map(|(bigram, count)| {
+                let mut chars = bigram.chars();
+                let (p1, f1) = index.get(&chars.next()?)?;
+                let (p2, f2) = index.get(&chars.next()?)?;
+
+                let adjacent = f1.hand() == f2.hand()
+                    && !f1.is_thumb()
+                    && !f2.is_thumb()
+                    && (*f1 as u8).abs_diff(*f2 as u8) == 1;
+
+                (adjacent && p1.row().abs_diff(p2.row()) >= 2).then_some(count)
+            })
+            .sum();
+
+        Ok(count)
+    }
+
+    /// Count the weighted amount of redirects on a layer, being trigrams typed with one hand where the
+    /// direction of finger movement changes, for example going from ring to index and then back to middle.
+    /// Trigrams that use the same finger twice in a row are not redirects. Trigrams containing characters
+    /// that aren't on the layer are ignored.
+    pub fn redirect_count(&self, layer: &str, trigrams: &[(String, u64)]) -> Result<u64> {
+        let index = self.char_fingers(layer)?;
+
+        let count = trigrams
+            .iter()
+            .filter_map(|(trigram, count)| {
+                let mut chars = trigram.chars();
+                let (_, f1) = index.get(&chars.next()?)?;
+                let (_, f2) = index.get(&chars.next()?)?;
+                let (_, f3) = index.get(&chars.next()?)?;
+
+                let same_hand = f1.hand() == f2.hand() && f2.hand() == f3.hand();
+                let first = (*f2 as u8).cmp(&(*f1 as u8));
+                let second = (*f3 as u8).cmp(&(*f2 as u8));
+
+                (same_hand && first.is_ne() && second.is_ne() && first != second).then_some(count)
+            })
+            .sum();
+
+        Ok(count)
+    }
+
+    /// Get the fraction of characters in a piece of text that can be typed on the base layer or the shift
+    /// layer, without switching to any other layer. If there is no shift layer, the one that would be
+    /// generated for the base layer is used. Spaces, newlines and tabs are typeable if the respective
+    /// special key is present. Returns 1.0 for empty text.
+    pub fn base_layer_coverage(&self, text: &str) -> Result<f64> {
+        let base = self.main_layer()?;
+        let shift = match self.layers.get("shift") {
+            Some(shift) => shift.clone(),
+            None => DofIntermediate::generate_shift_layer(base),
+        };
+
+        let typeable = base
+            .keys()
+            .chain(shift.keys())
+            .filter_map(|k| match k.tap() {
+                Key::Char(c) => Some(*c),
+                Key::Special(SpecialKey::Space) => Some(' '),
+                Key::Special(SpecialKey::Enter | SpecialKey::IsoEnter) => Some('\n'),
+                Key::Special(SpecialKey::Tab) => Some('\t'),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+
+        let (covered, total) = text.chars().fold((0usize, 0usize), |(covered, total), c| {
+            (covered + typeable.contains(&c) as usize, total + 1)
+        });
+
+        match total {
+            0 => Ok(1.0),
+            total => Ok(covered as f64 / total as f64),
+        }
+    }
+
+    /// Get the home position of every finger used on a layer. The home row is the middle row of the rows
+    /// that are pressed by fingers other than the thumbs, rounding down towards the bottom row, and a
+    /// finger's home is its position closest to the home row, where ties are broken by being
+    /// closest to the home of the middle finger on the same hand. This way the index finger rests on qwerty
+    /// `f` rather than `g`, and the pinky on `a` rather than caps lock.
+    pub(crate) fn home_positions(&self, layer: &str) -> Result<HashMap<Finger, Pos>> {
+        let fingering = self.unshaped_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let positions = layer
+            .rows()
+            .zip(fingering.rows())
+            .enumerate()
+            .flat_map(|(row, (keys, fingers))| {
+                fingers
+                    .iter()
+                    .take(keys.len())
+                    .enumerate()
+                    .map(move |(col, &f)| (f, Pos::new(row, col)))
+            })
+            .collect::<Vec<_>>();
+
+        let finger_rows = positions
+            .iter()
+            .filter(|(f, _)| !f.is_thumb())
+            .map(|(_, p)| p.row());
+        let home_row = match (finger_rows.clone().min(), finger_rows.max()) {
+            (Some(first), Some(last)) => (first + last).div_ceil(2),
+            _ => 0,
+        };
+
+        let closest = |finger: Finger, target_col: Option<usize>| {
+            positions
+                .iter()
+                .filter(|(f, _)| *f == finger)
+                .min_by_key(|(_, p)| {
+                    let col_dist = target_col.map(|c| p.col().abs_diff(c)).unwrap_or(p.col());
+                    (p.row().abs_diff(home_row), col_dist)
+                })
+                .map(|&(_, p)| p)
+        };
+
+        let middle = |finger: Finger| {
+            let middle = match finger.hand()? {
+                Hand::Left => Finger::LM,
+                Hand::Right => Finger::RM,
+            };
+            closest(middle, None).map(|p| p.col())
+        };
+
+        let homes = Finger::FINGERS
+            .into_iter()
+            .filter_map(|f| closest(f, middle(f)).map(|p| (f, p)))
+            .collect();
+
+        Ok(homes)
+    }
+
+    /// Get the total distance fingers travel to type a piece of text on a layer. Every finger starts on
+    /// its home position. Consecutive presses by the same finger count the distance between those keys,
+    /// while a press by a different finger counts the distance for the previous finger to return home,
+    /// plus the distance from home to the new key for the next one. Characters that aren't on the layer
+    /// are skipped. Returns an `InvalidPosition` error if the geometry doesn't cover a position that is
+    /// used.
+    pub fn total_travel(&self, layer: &str, text: &str, geometry: &Geometry) -> Result<f64> {
+        let index = self.char_fingers(layer)?;
+        let homes = self.home_positions(layer)?;
+
+        let distance = |p1: Pos, p2: Pos| covered_distance(geometry, p1, p2);
+
+        let mut travel = 0.0;
+        let mut prev: Option<(Finger, Pos)> = None;
+
+        for (pos, finger) in text.chars().filter_map(|c| index.get(&c)) {
+            let (pos, finger) = (*pos, *finger);
+            let home = homes.get(&finger).copied().unwrap_or(pos);
+
+            travel += match prev {
+                Some((f, p)) if f == finger => distance(p, pos)?,
+                Some((f, p)) => {
+                    let prev_home = homes.get(&f).copied().unwrap_or(p);
+                    distance(p, prev_home)? + distance(home, pos)?
+                }
+                None => distance(home, pos)?,
+            };
+
+            prev = Some((finger, pos));
+        }
+
+        Ok(travel)
+    }
+
+    /// Get the total distance of the same finger bigrams of a corpus on a layer, being the sum of the
+    /// distance between both keys of every same finger bigram multiplied by its frequency. Repeating the
+    /// same key isn't a same finger bigram, and bigrams containing characters that aren't on the layer are
+    /// ignored. Returns an `InvalidPosition` error if the geometry doesn't cover a position that is used.
+    pub fn weighted_bigram_distance(
+        &self,
+        layer: &str,
+        bigrams: &Corpus,
+        geometry: &Geometry,
+    ) -> Result<f64> {
+        let index = self.char_fingers(layer)?;
+
+        let mut distance = 0.0;
+
+        for (bigram, &count) in bigrams.bigrams() {
+            let mut chars = bigram.chars();
+            let first = chars.next().and_then(|c| index.get(&c));
+            let second = chars.next().and_then(|c| index.get(&c));
+
+            if let (Some(&(p1, f1)), Some(&(p2, f2))) = (first, second) {
+                if f1 == f2 && p1 != p2 {
+                    distance += covered_distance(geometry, p1, p2)? * count as f64;
+                }
+            }
+        }
+
+        Ok(distance)
+    }
+
+    /// Get the length of the longest run of consecutive characters in a piece of text that are typed by
+    /// the same finger on different keys, like `ded` on qwerty, which is a run of 3. Pressing the same key
+    /// twice or a character that isn't on the layer ends a run. If the text doesn't contain a single same
+    /// finger bigram, this is 0.
+    pub fn longest_sfb_run(&self, layer: &str, text: &str) -> Result<usize> {
+        let index = self.char_fingers(layer)?;
+
+        let mut longest = 0;
+        let mut run = 0;
+        let mut prev: Option<(Pos, Finger)> = None;
+
+        for c in text.chars() {
+            let current = index.get(&c).copied();
+
+            run = match (prev, current) {
+                (Some((p1, f1)), Some((p2, f2))) if f1 == f2 && p1 != p2 => run.max(1) + 1,
+                _ => 0,
+            };
+            longest = longest.max(run);
+            prev = current;
+        }
+
+        Ok(longest)
+    }
+
+    /// Flatten a layer into a numeric feature vector, for example to use as input for machine learning.
+    /// The vector always covers the whole physical board, so layouts on the same board produce vectors of
+    /// the same length where the same index refers to the same physical key.
+    ///
+    /// Positions of the board are visited in row-major order, where the layer is placed on the board using
+    /// the anchor. Each position contributes [`FEATURES_PER_KEY`](crate::analysis::FEATURES_PER_KEY) values:
+    /// first the index of the finger pressing it (0 for `LP` through 9 for `RP`, -1 for `Finger::Unassigned`),
+    /// then a one-hot encoding of the key being empty, transparent, a character, a word, a special key or
+    /// a layer key, in that order, where markers count as empty. Positions the layer doesn't cover are
+    /// padded with a finger index of -1 and all zeroes, and keys that fall outside of the board are left
+    /// out.
+    pub fn feature_matrix(&self, layer: &str) -> Result<Vec<f32>> {
+        let fingering = self.explicit_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+        let anchor = self.resolved_anchor()?;
+        let board = PhysicalKeyboard::try_from(self.board.clone())?;
+
+        let mut features = Vec::with_capacity(board.keys().count() * FEATURES_PER_KEY);
+
+        for (row, board_row) in board.rows().enumerate() {
+            for col in 0..board_row.len() {
+                let key = row
+                    .checked_sub(anchor.y())
+                    .zip(col.checked_sub(anchor.x()))
+                    .and_then(|(r, c)| {
+                        let key = layer.inner().get(r)?.get(c)?;
+                        let finger = fingering.inner().get(r)?.get(c)?;
+                        Some((key, finger))
+                    });
+
+                match key {
+                    Some((key, &finger)) => {
+                        match finger {
+                            Finger::Unassigned => features.push(-1.0),
+                            f => features.push(f as u8 as f32),
+                        }
+                        features.extend(key_category(key));
+                    }
+                    None => {
+                        features.push(-1.0);
+                        features.extend([0.0; FEATURES_PER_KEY - 1]);
+                    }
+                }
+            }
+        }
+
+        Ok(features)
+    }
+
+    /// Count the weighted amount of same finger bigrams on a layer, being bigrams typed by the same finger
+    /// on two different keys. Bigrams containing characters that aren't on the layer are ignored.
+    pub fn sfb_count(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_sfbs(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Get every pair of characters on a layer that are typed by the same finger on two different keys,
+    /// meaning they form a same finger bigram when typed after one another. This doesn't depend on any
+    /// corpus, so it shows every potential sfb of a layer. Each pair is listed once, in character order.
+    pub fn structural_sfbs(&self, layer: &str) -> Result<Vec<(char, char)>> {
+        let index = self.char_fingers(layer)?;
+
+        let sfbs = index
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (&c1, (p1, f1)))| {
+                index
+                    .iter()
+                    .skip(i + 1)
+                    .filter(move |(_, (p2, f2))| f1 == f2 && p1 != p2)
+                    .map(move |(&c2, _)| (c1, c2))
+            })
+            .collect();
+
+        Ok(sfbs)
+    }
+
+    /// Get a copy of the layout where a layer is typed with a provided fingering instead of its own, by
+    /// setting it in `fingerings`. Every analysis method can then be run on the copy to analyze the layer
+    /// with that fingering, while the `_with_fingering` variants avoid the copy for the most common ones.
+    /// Returns an `IncompatibleFingeringShape` error if the fingering doesn't have the same shape as the
+    /// layer.
+    pub fn with_fingering(&self, layer: &str, fingering: &Fingering) -> Result<DofIntermediate> {
+        if fingering.shape() != self.layer_or_err(layer)?.shape() {
+            return Err(DE::IncompatibleFingeringShape.into());
+        }
+
+        let mut inter = self.clone();
+        inter.fingerings.get_or_insert_with(BTreeMap::new).insert(
+            layer.to_owned(),
+            ParsedFingering::Explicit(fingering.clone()),
+        );
+        inter.refresh_checksum();
+
+        Ok(inter)
+    }
+
+    /// Like [`sfb_count()`](crate::DofIntermediate::sfb_count), but with a provided fingering instead
+    /// of the layout's own. The fingering must have the same shape as the layer.
+    pub fn sfb_count_with_fingering(
+        &self,
+        layer: &str,
+        fingering: &Fingering,
+        corpus: &Corpus,
+    ) -> Result<u64> {
+        Ok(count_sfbs(
+            &self.char_fingers_with(layer, fingering)?,
+            corpus,
+        ))
+    }
+
+    /// Count the weighted amount of bigrams on a layer that are rolls, as described in
+    /// [`is_roll()`](crate::DofIntermediate::is_roll), in either direction. Bigrams containing characters
+    /// that aren't on the layer are ignored.
+    pub fn roll_count(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_rolls(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Get the fraction of rolls on a layer that roll inward, being `inward / (inward + outward)`, where
+    /// both are counted like [`roll_count()`](crate::DofIntermediate::roll_count). If there are no rolls,
+    /// this is 0.0.
+    pub fn roll_ratio(&self, layer: &str, corpus: &Corpus) -> Result<f64> {
+        let index = self.char_fingers(layer)?;
+
+        let inward = count_bigrams(&index, corpus, |(_, f1), (_, f2)| {
+            roll_kind(*f1, *f2) == Some(RollKind::Inward)
+        });
+
+        match count_rolls(&index, corpus) {
+            0 => Ok(0.0),
+            total => Ok(inward as f64 / total as f64),
+        }
+    }
+
+    /// Like [`roll_count()`](crate::DofIntermediate::roll_count), but with a provided fingering instead
+    /// of the layout's own. The fingering must have the same shape as the layer.
+    pub fn roll_count_with_fingering(
+        &self,
+        layer: &str,
+        fingering: &Fingering,
+        corpus: &Corpus,
+    ) -> Result<u64> {
+        Ok(count_rolls(
+            &self.char_fingers_with(layer, fingering)?,
+            corpus,
+        ))
+    }
+
+    /// Count the weighted amount of bigrams on a layer that alternate hands, where thumbs count as part of
+    /// their hand. Bigrams containing characters that aren't on the layer are ignored.
+    pub fn alternation(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_alternation(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Like [`alternation()`](crate::DofIntermediate::alternation), but with a provided fingering
+    /// instead of the layout's own. The fingering must have the same shape as the layer.
+    pub fn alternation_with_fingering(
+        &self,
+        layer: &str,
+        fingering: &Fingering,
+        corpus: &Corpus,
+    ) -> Result<u64> {
+        Ok(count_alternation(
+            &self.char_fingers_with(layer, fingering)?,
+            corpus,
+        ))
+    }
+
+    /// Compare a layer typed with two different fingerings, like `angle` and `traditional`, by their
+    /// same finger bigrams, rolls and alternation. Both fingerings must have the same shape as the layer.
+    /// Named fingerings can be turned into a `Fingering` with
+    /// [`KeyboardType::fingering()`](crate::dofinitions::KeyboardType::fingering).
+    pub fn compare_fingerings(
+        &self,
+        layer: &str,
+        a: &Fingering,
+        b: &Fingering,
+        corpus: &Corpus,
+    ) -> Result<FingeringComparison> {
+        let stats = |fingering| -> Result<FingeringStats> {
+            let index = self.char_fingers_with(layer, fingering)?;
+
+            Ok(FingeringStats {
+                sfbs: count_sfbs(&index, corpus),
+                rolls: count_rolls(&index, corpus),
+                alternation: count_alternation(&index, corpus),
+            })
+        };
+
+        Ok(FingeringComparison {
+            a: stats(a)?,
+            b: stats(b)?,
+        })
+    }
+}
+
+impl Dof {
+    /// Map every character on a layer to its position and finger, using the fingering that was validated
+    /// when the `Dof` was created, so it doesn't need to be checked against the layer again.
+    fn char_fingers(&self, layer: &str) -> Result<BTreeMap<char, (Pos, Finger)>> {
+        let fingering = self.fingering_for(layer);
+        let layer = self
+            .layer(layer)
+            .ok_or(DE::LayerDoesntExist(layer.into()))?;
+
+        Ok(char_index(layer, fingering))
+    }
+
+    /// Count the weighted amount of same finger bigrams on a layer, like
+    /// [`DofIntermediate::sfb_count()`](crate::DofIntermediate::sfb_count). As the shape of the layers
+    /// and fingering was validated when creating the `Dof`, this only fails if the layer doesn't exist,
+    /// which makes it cheaper for repeated analysis.
+    pub fn sfb_count(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_sfbs(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Count the weighted amount of rolls on a layer, like
+    /// [`DofIntermediate::roll_count()`](crate::DofIntermediate::roll_count). Only fails if the layer
+    /// doesn't exist.
+    pub fn roll_count(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_rolls(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Count the weighted amount of bigrams that alternate hands on a layer, like
+    /// [`DofIntermediate::alternation()`](crate::DofIntermediate::alternation). Only fails if the layer
+    /// doesn't exist.
+    pub fn alternation(&self, layer: &str, corpus: &Corpus) -> Result<u64> {
+        Ok(count_alternation(&self.char_fingers(layer)?, corpus))
+    }
+
+    /// Get the fraction of key presses done by each finger on a layer, like
+    /// [`DofIntermediate::finger_usage()`](crate::DofIntermediate::finger_usage). Only fails if the
+    /// layer doesn't exist, as the thumb cluster was validated when creating the `Dof` as well.
+    pub fn finger_usage(
+        &self,
+        layer: &str,
+        unigrams: &[(char, u64)],
+        include_thumbs: bool,
+    ) -> Result<[f64; 10]> {
+        let mut index = self.char_fingers(layer)?;
+
+        if include_thumbs {
+            add_thumbs(&mut index, &self.thumbs, &self.thumb_fingering);
+        }
+
+        Ok(count_finger_usage(&index, unigrams))
+    }
+}
+
+/// Map every character on a layer to its position and finger, skipping keys pressed with `Finger::Unassigned`.
+fn char_index(layer: &Layer, fingering: &Fingering) -> BTreeMap<char, (Pos, Finger)> {
+    let mut map = BTreeMap::new();
+
+    for (row, (key_row, finger_row)) in layer.rows().zip(fingering.rows()).enumerate() {
+        for (col, (key, &finger)) in key_row.iter().zip(finger_row).enumerate() {
+            if let (Key::Char(c), false) = (key.tap(), finger.is_unassigned()) {
+                map.entry(*c).or_insert((Pos::new(row, col), finger));
+            }
+        }
+    }
+
+    map
+}
+
+/// Add the characters of a thumb cluster to a character index, unless they're already in it.
+fn add_thumbs(
+    index: &mut BTreeMap<char, (Pos, Finger)>,
+    thumbs: &Option<Layer>,
+    fingering: &Option<Fingering>,
+) {
+    if let (Some(thumbs), Some(fingering)) = (thumbs, fingering) {
+        for (c, entry) in char_index(thumbs, fingering) {
+            index.entry(c).or_insert(entry);
+        }
+    }
+}
+
+/// Get the fraction of weighted unigrams typed by each finger, in the order of `Finger::FINGERS`.
+fn count_finger_usage(
+    index: &BTreeMap<char, (Pos, Finger)>,
+    unigrams: &[(char, u64)],
+) -> [f64; 10] {
+    let mut usage = [0u64; 10];
+
+    for (c, count) in unigrams {
+        if let Some(i) = index
+            .get(c)
+            .and_then(|(_, f)| Finger::FINGERS.iter().position(|finger| finger == f))
+        {
+            usage[i] += count;
+        }
+    }
+
+    let total = usage.iter().sum::<u64>();
+
+    usage.map(|count| match total {
+        0 => 0.0,
+        total => count as f64 / total as f64,
+    })
+}
+
+/// Get the distance between two positions on a geometry. Returns an `InvalidPosition` error containing
+/// the first of the two positions the geometry doesn't cover, if any.
+fn covered_distance(geometry: &Geometry, p1: Pos, p2: Pos) -> Result<f64> {
+    geometry.distance(p1, p2).ok_or_else(|| {
+        let uncovered = match geometry.position(p1) {
+            None => p1,
+            Some(_) => p2,
+        };
+
+        DE::InvalidPosition(uncovered.row(), uncovered.col()).into()
+    })
+}
+
+/// Direction of the roll between two fingers, if they form one.
+fn roll_kind(f1: Finger, f2: Finger) -> Option<RollKind> {
+    if f1.hand() != f2.hand() || f1 == f2 || f1.is_thumb() || f2.is_thumb() {
+        return None;
+    }
+
+    let towards_right = (f2 as u8) > (f1 as u8);
+
+    match (f1.hand()?, towards_right) {
+        (Hand::Left, true) | (Hand::Right, false) => Some(RollKind::Inward),
+        _ => Some(RollKind::Outward),
+    }
+}
+
+/// Sum the counts of the bigrams of a corpus for which `f` returns true, given the position and finger of
+/// both characters. Bigrams with characters that aren't in `index` are skipped.
+fn count_bigrams(
+    index: &BTreeMap<char, (Pos, Finger)>,
+    corpus: &Corpus,
+    f: impl Fn(&(Pos, Finger), &(Pos, Finger)) -> bool,
+) -> u64 {
+    corpus
+        .bigrams()
+        .iter()
+        .filter_map(|(bigram, &count)| {
+            let mut chars = bigram.chars();
+            let first = index.get(&chars.next()?)?;
+            let second = index.get(&chars.next()?)?;
+
+            f(first, second).then_some(count)
+        })
+        .sum()
+}
+
+/// Sum the counts of the same finger bigrams of a corpus, being bigrams typed by the same finger on two
+/// different keys.
+fn count_sfbs(index: &BTreeMap<char, (Pos, Finger)>, corpus: &Corpus) -> u64 {
+    count_bigrams(index, corpus, |(p1, f1), (p2, f2)| f1 == f2 && p1 != p2)
+}
+
+/// Sum the counts of the bigrams of a corpus that roll in either direction, as decided by
+/// [`roll_kind()`].
+fn count_rolls(index: &BTreeMap<char, (Pos, Finger)>, corpus: &Corpus) -> u64 {
+    count_bigrams(index, corpus, |(_, f1), (_, f2)| {
+        roll_kind(*f1, *f2).is_some()
+    })
+}
+
+/// Sum the counts of the bigrams of a corpus that are typed with different hands.
+fn count_alternation(index: &BTreeMap<char, (Pos, Finger)>, corpus: &Corpus) -> u64 {
+    count_bigrams(index, corpus, |(_, f1), (_, f2)| f1.hand() != f2.hand())
+}
+
+/// One-hot encoding of the kind of key, used by
+/// [`feature_matrix()`](crate::DofIntermediate::feature_matrix).
+fn key_category(key: &Key) -> [f32; FEATURES_PER_KEY - 1] {
+    let index = match key {
+        Key::Empty | Key::Marker(_) => 0,
+        Key::Transparent => 1,
+        Key::Char(_) => 2,
+        Key::Word(_) => 3,
+        Key::Special(_) => 4,
+        Key::Layer { .. } => 5,
+        Key::TapHold { tap, .. } => return key_category(tap),
+    };
+
+    let mut category = [0.0; FEATURES_PER_KEY - 1];
+    category[index] = 1.0;
+    category
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static MINIMAL: &str = include_str!("../example_dofs/minimal_valid.dof");
+
+    #[test]
+    fn corpus_from_text() {
+        let corpus = Corpus::from_text("abab");
+
+        assert_eq!(corpus.unigrams().get("a"), Some(&2));
+        assert_eq!(corpus.bigrams().get("ab"), Some(&2));
+        assert_eq!(corpus.bigrams().get("ba"), Some(&1));
+        assert_eq!(corpus.trigrams().get("aba"), Some(&1));
+        assert_eq!(corpus.trigrams().len(), 2);
+    }
+
+    #[test]
+    fn validated_analysis() {
+        let aptmak = include_str!("../example_dofs/aptmak.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(aptmak).expect("couldn't parse json");
+        let dof = Dof::try_from(inter.clone()).expect("couldn't validate");
+
+        let corpus = Corpus::from_text("the quick brown fox jumps over the lazy dog");
+        let unigrams = [('e', 3), ('t', 2), ('q', 1)];
+
+        assert_eq!(
+            dof.sfb_count("main", &corpus),
+            inter.sfb_count("main", &corpus)
+        );
+        assert_eq!(
+            dof.roll_count("main", &corpus),
+            inter.roll_count("main", &corpus)
+        );
+        assert_eq!(
+            dof.alternation("main", &corpus),
+            inter.alternation("main", &corpus)
+        );
+        assert_eq!(
+            dof.finger_usage("main", &unigrams, true),
+            inter.finger_usage("main", &unigrams, true)
+        );
+        assert!(dof.sfb_count("nope", &corpus).is_err());
+    }
+
+    #[test]
+    fn section_balance() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        assert_eq!(inter.section_balance("main"), Ok((15, 0, 16)));
+
+        let aptmak = include_str!("../example_dofs/aptmak.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(aptmak).expect("couldn't parse json");
+        let (left, thumbs, right) = inter.section_balance("main").unwrap();
+
+        assert_eq!(thumbs, 2);
+        assert_eq!(left + thumbs + right, 32);
+        assert!(inter.section_balance("nope").is_err());
+    }
+
+    #[test]
+    fn finger_usage() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        inter.thumbs = Some(vec![vec![Key::Char('_'), Key::Char('a')]].into());
+        inter.thumb_fingering = Some(vec![vec![Finger::LT, Finger::RT]].into());
+
+        let unigrams = [('a', 1), ('_', 1), ('€', 5)];
+
+        let without = inter.finger_usage("main", &unigrams, false).unwrap();
+        assert_eq!(without, [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        let with = inter.finger_usage("main", &unigrams, true).unwrap();
+        assert_eq!(with, [0.5, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(inter.finger_usage("main", &[], true).unwrap(), [0.0; 10]);
+
+        inter.thumb_fingering = Some(vec![vec![Finger::LT]].into());
+        assert!(inter.finger_usage("main", &unigrams, true).is_err());
+        assert!(inter.finger_usage("main", &unigrams, false).is_ok());
+    }
+
+    #[test]
+    fn weighted_finger_load() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let mut grid = inter
+            .main_layer()
+            .unwrap()
+            .rows()
+            .map(|row| vec![1.0; row.len()])
+            .collect::<Vec<_>>();
+        grid[1][0] = 2.0;
+
+        let unigrams = [('a', 3), ('s', 1), ('€', 5)];
+        let load = inter
+            .weighted_finger_load("main", &unigrams, &grid.clone().into())
+            .unwrap();
+
+        assert_eq!(load.len(), 10);
+        assert_eq!(load[&Finger::LP], 6.0);
+        assert_eq!(load[&Finger::LR], 1.0);
+        assert_eq!(load[&Finger::RP], 0.0);
+
+        grid.pop();
+        assert_eq!(
+            inter.weighted_finger_load("main", &unigrams, &grid.into()),
+            Err(DE::IncompatibleEffortShape.into())
+        );
+    }
+
+    #[test]
+    fn finger_disbalance() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let even = "qwerasdfuiopjkl;"
+            .chars()
+            .map(|c| (c, 1))
+            .collect::<Vec<_>>();
+        assert_eq!(inter.finger_disbalance("main", &even), Ok(0.0));
+
+        let pinky = inter.finger_disbalance("main", &[('a', 1)]).unwrap();
+        assert!((pinky - 7f64.sqrt()).abs() < 1e-9);
+
+        assert_eq!(inter.finger_disbalance("main", &[]), Ok(0.0));
+        assert!(inter.finger_disbalance("altgr", &even).is_err());
+    }
+
+    #[test]
+    fn finger_load_fractions() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let corpus = Corpus::from_text("aaas€");
+        let fractions = inter.finger_load_fractions("main", &corpus).unwrap();
+
+        assert_eq!(fractions.len(), 10);
+        assert_eq!(fractions[&Finger::LP], 0.75);
+        assert_eq!(fractions[&Finger::LR], 0.25);
+        assert_eq!(fractions.values().sum::<f64>(), 1.0);
+
+        let empty = inter
+            .finger_load_fractions("main", &Corpus::from_text(""))
+            .unwrap();
+        assert!(empty.values().all(|&f| f == 0.0));
+        assert!(inter.finger_load_fractions("altgr", &corpus).is_err());
+    }
+
+    #[test]
+    fn feature_matrix() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let features = inter
+            .feature_matrix("main")
+            .expect("couldn't get feature matrix");
+        let start = |row: usize, col: usize| ([0, 14, 28, 41][row] + col) * FEATURES_PER_KEY;
+        let at =
+            |features: &[f32], row, col| features[start(row, col)..][..FEATURES_PER_KEY].to_vec();
+
+        assert_eq!(features.len(), (14 + 14 + 13 + 12 + 8) * FEATURES_PER_KEY);
+        assert_eq!(at(&features, 0, 0), [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(at(&features, 1, 0), [-1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(at(&features, 1, 1), [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(at(&features, 2, 11), [9.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(at(&features, 3, 1), [1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+
+        let mut other = inter.clone();
+        other.layers.get_mut("main").unwrap().0[0][0] = Key::Special(SpecialKey::Space);
+
+        let other_features = other
+            .feature_matrix("main")
+            .expect("couldn't get feature matrix");
+
+        assert_eq!(other_features.len(), features.len());
+        assert_eq!(
+            at(&other_features, 1, 1),
+            [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+        );
+        assert_eq!(other_features[..start(1, 1)], features[..start(1, 1)]);
+        assert_eq!(other_features[start(1, 2)..], features[start(1, 2)..]);
+
+        assert!(inter.feature_matrix("altgr").is_err());
+    }
+
+    #[test]
+    fn hand_usage() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.hand_usage("main", &[('a', 3), ('j', 1), ('ß', 100)]),
+            Ok((0.75, 0.25))
+        );
+        assert_eq!(inter.hand_usage("main", &[('ß', 100)]), Ok((0.0, 0.0)));
+        assert_eq!(inter.hand_usage("main", &[]), Ok((0.0, 0.0)));
+        assert!(inter.hand_usage("altgr", &[('a', 1)]).is_err());
+    }
+
+    #[test]
+    fn heatmap() {
+        let aptmak = include_str!("../example_dofs/aptmak.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(aptmak).expect("couldn't parse json");
+
+        let heatmap = inter
+            .heatmap(
+                "main",
+                &[('e', 4), ('v', 1), ('w', 1), ('v', 1), ('ß', 100)],
+            )
+            .expect("couldn't get heatmap");
+
+        assert_eq!(
+            heatmap.iter().map(Vec::len).collect::<Vec<_>>(),
+            [10, 10, 10, 6]
+        );
+        assert_eq!(heatmap[0][..3], [0.5, 0.25, 0.0]);
+        assert_eq!(heatmap[3], [0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+
+        let empty = inter.heatmap("main", &[]).expect("couldn't get heatmap");
+        assert!(empty.iter().flatten().all(|&f| f == 0.0));
+
+        assert!(inter.heatmap("altgr", &[]).is_err());
+    }
+
+    #[test]
+    fn compare_fingerings() {
+        use crate::dofinitions::{KeyboardType, NamedFingering};
+
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let main = inter.main_layer().unwrap();
+        let anchor = inter.resolved_anchor().unwrap();
+
+        let fingering = |named| -> Fingering {
+            KeyboardType::Ansi
+                .fingering(&named)
+                .unwrap()
+                .resized(anchor, main.shape())
+                .unwrap()
+                .into()
+        };
+        let angle = fingering(NamedFingering::Angle);
+        let traditional = fingering(NamedFingering::Traditional);
+
+        let corpus = Corpus::from_text("zxcv bzb");
+
+        assert_eq!(
+            inter.sfb_count_with_fingering("main", &angle, &corpus),
+            inter.sfb_count("main", &corpus)
+        );
+        assert_eq!(
+            inter.roll_count_with_fingering("main", &angle, &corpus),
+            inter.roll_count("main", &corpus)
+        );
+        assert_eq!(
+            inter.alternation_with_fingering("main", &angle, &corpus),
+            inter.alternation("main", &corpus)
+        );
+
+        let comparison = inter
+            .compare_fingerings("main", &traditional, &angle, &corpus)
+            .expect("couldn't compare fingerings");
+
+        assert_eq!(
+            comparison.a,
+            FingeringStats {
+                sfbs: 0,
+                rolls: 5,
+                alternation: 0
+            }
+        );
+        assert_eq!(
+            comparison.b,
+            FingeringStats {
+                sfbs: 1,
+                rolls: 4,
+                alternation: 0
+            }
+        );
+        assert_eq!(comparison.sfb_delta(), 1);
+        assert_eq!(comparison.roll_delta(), -1);
+        assert_eq!(comparison.alternation_delta(), 0);
+
+        let small = Fingering::from(vec![vec![Finger::LP]]);
+        assert_eq!(
+            inter.compare_fingerings("main", &small, &angle, &corpus),
+            Err(DE::IncompatibleFingeringShape.into())
+        );
+        assert_eq!(
+            inter.with_fingering("main", &small),
+            Err(DE::IncompatibleFingeringShape.into())
+        );
+
+        // Every bigram of `lake` switches hands on qwerty, as do all but `wa` of `they wake`. The space
+        // isn't on the layer, so bigrams containing it are ignored.
+        let mixed = Corpus::from_text("lake");
+        assert_eq!(
+            inter.alternation_with_fingering("main", &angle, &mixed),
+            Ok(3)
+        );
+        assert_eq!(
+            inter.alternation_with_fingering("main", &angle, &Corpus::from_text("they wake")),
+            Ok(5)
+        );
+
+        // With every key on the left hand nothing alternates anymore.
+        let left = Fingering::from(
+            main.rows()
+                .map(|row| vec![Finger::LI; row.len()])
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(
+            inter.alternation_with_fingering("main", &left, &mixed),
+            Ok(0)
+        );
+
+        let with_left = inter
+            .with_fingering("main", &left)
+            .expect("couldn't set fingering");
+        assert_eq!(with_left.alternation("main", &mixed), Ok(0));
+        assert_eq!(
+            with_left.hand_usage("main", &[('l', 1), ('a', 1)]),
+            Ok((1.0, 0.0))
+        );
+        assert_eq!(
+            inter.hand_usage("main", &[('l', 1), ('a', 1)]),
+            Ok((0.5, 0.5))
+        );
+    }
+
+    #[test]
+    fn is_roll() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.is_roll("main", 'a', 'f'), Ok(Some(RollKind::Inward)));
+        assert_eq!(inter.is_roll("main", 'f', 's'), Ok(Some(RollKind::Outward)));
+        assert_eq!(inter.is_roll("main", 'l', 'j'), Ok(Some(RollKind::Inward)));
+        assert_eq!(inter.is_roll("main", 'j', ';'), Ok(Some(RollKind::Outward)));
+        assert_eq!(inter.is_roll("main", 'f', 'g'), Ok(None));
+        assert_eq!(inter.is_roll("main", 'a', 'j'), Ok(None));
+        assert_eq!(inter.is_roll("main", 'a', '!'), Ok(None));
+        assert!(inter.is_roll("altgr", 'a', 's').is_err());
+    }
+
+    #[test]
+    fn weighted_merge() {
+        let english = Corpus::from_text("the");
+        let dutch = Corpus::from_text("het");
+
+        let merged = Corpus::weighted_merge(&[(english, 2.0), (dutch, 0.5)]);
+
+        assert_eq!(merged.unigrams().get("t"), Some(&3));
+        assert_eq!(merged.bigrams().get("th"), Some(&2));
+        assert_eq!(merged.bigrams().get("he"), Some(&3));
+        assert_eq!(merged.bigrams().get("et"), Some(&1));
+    }
+
+    #[test]
+    fn sfb_count_and_alternation() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let corpus = Corpus::from_text("ed de ll the");
+
+        assert_eq!(inter.sfb_count("main", &corpus), Ok(2));
+        assert_eq!(inter.alternation("main", &Corpus::from_text("the")), Ok(2));
+        assert!(inter.sfb_count("altgr", &corpus).is_err());
+    }
+
+    #[test]
+    fn structural_sfbs() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let sfbs = inter.structural_sfbs("main").unwrap();
+
+        assert!(sfbs.contains(&('d', 'e')));
+        assert!(!sfbs.contains(&('e', 'd')));
+        assert!(!sfbs.contains(&('a', 's')));
+        assert!(sfbs
+            .iter()
+            .all(|&(a, b)| a < b
+                && inter.finger_for_char("main", a) == inter.finger_for_char("main", b)));
+        assert!(inter.structural_sfbs("altgr").is_err());
+    }
+
+    #[test]
+    fn longest_sfb_run() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.longest_sfb_run("main", "ded"), Ok(3));
+        assert_eq!(inter.longest_sfb_run("main", "deed"), Ok(2));
+        assert_eq!(inter.longest_sfb_run("main", "ded frfr"), Ok(4));
+        assert_eq!(inter.longest_sfb_run("main", "asdf"), Ok(0));
+        assert_eq!(inter.longest_sfb_run("main", ""), Ok(0));
+        assert!(inter.longest_sfb_run("altgr", "ded").is_err());
+    }
+
+    #[test]
+    fn roll_ratio() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.roll_ratio("main", &Corpus::from_text("as as as sa")),
+            Ok(0.75)
+        );
+        assert_eq!(inter.roll_ratio("main", &Corpus::from_text("aj")), Ok(0.0));
+        assert!(inter.roll_ratio("altgr", &Corpus::default()).is_err());
+    }
+
+    #[test]
+    fn same_hand_bigrams() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let bigrams = [
+            ("ed".to_string(), 10),
+            ("th".to_string(), 5),
+            ("he".to_string(), 3),
+            ("ll".to_string(), 2),
+            ("é!".to_string(), 100),
+        ];
+
+        assert_eq!(inter.same_hand_bigrams("main", &bigrams), Ok(12));
+        assert!(inter.same_hand_bigrams("altgr", &bigrams).is_err());
+    }
+
+    #[test]
+    fn scissor_count() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let bigrams = [
+            ("wx".to_string(), 3),
+            ("ec".to_string(), 2),
+            ("im".to_string(), 1),
+            ("ed".to_string(), 5),
+            ("ex".to_string(), 7),
+            ("qb".to_string(), 11),
+        ];
+
+        assert_eq!(inter.scissor_count("main", &bigrams), Ok(6));
+        assert!(inter.scissor_count("altgr", &bigrams).is_err());
+    }
+
+    #[test]
+    fn redirect_count() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let trigrams = [
+            ("sfd".to_string(), 3),
+            ("dsf".to_string(), 2),
+            ("lkp".to_string(), 1),
+            ("sdf".to_string(), 5),
+            ("ded".to_string(), 7),
+            ("sfj".to_string(), 11),
+        ];
+
+        assert_eq!(inter.redirect_count("main", &trigrams), Ok(6));
+        assert!(inter.redirect_count("altgr", &trigrams).is_err());
+    }
+
+    #[test]
+    fn base_layer_coverage() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.base_layer_coverage("Hello;"), Ok(1.0));
+        assert_eq!(inter.base_layer_coverage("hé!!"), Ok(0.25));
+        assert_eq!(inter.base_layer_coverage(""), Ok(1.0));
+
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(inter.base_layer_coverage("Über ß\n"), Ok(6.0 / 7.0));
+    }
+
+    #[test]
+    fn home_positions() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let homes = inter.home_positions("main").expect("couldn't get homes");
+
+        assert_eq!(homes.len(), 8);
+        assert_eq!(homes[&Finger::LP], Pos::new(1, 0));
+        assert_eq!(homes[&Finger::LI], Pos::new(1, 3));
+        assert_eq!(homes[&Finger::RI], Pos::new(1, 6));
+        assert_eq!(homes[&Finger::RP], Pos::new(1, 9));
+
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+        let homes = inter.home_positions("main").expect("couldn't get homes");
+
+        assert_eq!(homes[&Finger::LP], Pos::new(2, 1));
+        assert_eq!(homes[&Finger::LI], Pos::new(2, 4));
+        assert_eq!(homes[&Finger::LT], Pos::new(4, 3));
+    }
+
+    #[test]
+    fn total_travel() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let geometry = Geometry::default();
+
+        assert_eq!(inter.total_travel("main", "asdf", &geometry), Ok(0.0));
+        assert_eq!(inter.total_travel("main", "fr", &geometry), Ok(1.0));
+        assert_eq!(inter.total_travel("main", "frf", &geometry), Ok(2.0));
+        assert_eq!(inter.total_travel("main", "rj", &geometry), Ok(2.0));
+        assert_eq!(inter.total_travel("main", "fé", &geometry), Ok(0.0));
+        assert!(inter.total_travel("altgr", "f", &geometry).is_err());
+
+        let top_row = serde_json::from_str::<Geometry>(
+            r#"{"keys": [["0 0", "1 0", "2 0", "3 0", "4 0", "5 0", "6 0", "7 0", "8 0", "9 0"]]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            inter.total_travel("main", "rf", &top_row),
+            Err(DE::InvalidPosition(1, 3).into())
+        );
+    }
+    #[test]
+    fn weighted_bigram_distance() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let geometry = Geometry::default();
+        let distance = |text| {
+            inter
+                .weighted_bigram_distance("main", &Corpus::from_text(text), &geometry)
+                .unwrap()
+        };
+
+        assert_eq!(distance("fr"), 1.0);
+        assert_eq!(distance("frfr"), 3.0);
+        assert_eq!(distance("ff as"), 0.0);
+        assert_eq!(distance("fé"), 0.0);
+        assert!(inter
+            .weighted_bigram_distance("altgr", &Corpus::from_text("fr"), &geometry)
+            .is_err());
+
+        let top_row = serde_json::from_str::<Geometry>(
+            r#"{"keys": [["0 0", "1 0", "2 0", "3 0", "4 0", "5 0", "6 0", "7 0", "8 0", "9 0"]]}"#,
+        )
+        .unwrap();
+
+        for text in ["fr", "rf"] {
+            assert_eq!(
+                inter.weighted_bigram_distance("main", &Corpus::from_text(text), &top_row),
+                Err(DE::InvalidPosition(1, 3).into())
+            );
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Contains a compact binary serialization of layouts using [`postcard`](https://crates.io/crates/postcard),
+//! which is useful for caching parsed layouts. Requires the `binary` feature.
+//!
+//! Rather than storing rows as joined strings like in a .dof file, layers and fingerings are stored as
+//! their underlying keys and fingers directly.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dofinitions::{Finger, Key, KeyboardType, NamedFingering},
+    keyboard::{Geometry, ParseKeyboard, PhysicalKey, RelativeKey},
+    Anchor, Dof, DofError, DofErrorInner as DErr, DofIntermediate, Fingering, Keyboard, Language,
+    Layer, LayerMetadata, ParsedAnchor, ParsedFingering, Result, RowNotation,
+};
+
+#[derive(Serialize, Deserialize)]
+struct BinaryDof {
+    name: String,
+    version: Option<String>,
+    authors: Option<Vec<String>>,
+    board: BinaryBoard,
+    year: Option<u32>,
+    description: Option<String>,
+    languages: Option<Vec<Language>>,
+    link: Option<String>,
+    tags: Option<Vec<String>>,
+    corpus: Option<String>,
+    layers: Vec<(String, BinaryLayer)>,
+    base_layer: Option<String>,
+    anchor: Option<BinaryAnchor>,
+    geometry: Option<BinaryGeometry>,
+    key_ids: Option<Vec<Vec<u16>>>,
+    thumbs: Option<BinaryLayer>,
+    thumb_fingering: Option<BinaryFingers>,
+    auto_shift: Option<bool>,
+    case_insensitive_layers: Option<bool>,
+    fingering: Option<BinaryFingering>,
+    fingerings: Option<Vec<(String, BinaryFingering)>>,
+    locked: Option<Vec<Anchor>>,
+    checksum: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryLayer {
+    keys: Vec<Vec<Key>>,
+    color: Option<String>,
+    description: Option<String>,
+}
+
+impl From<&Layer> for BinaryLayer {
+    fn from(layer: &Layer) -> Self {
+        BinaryLayer {
+            keys: layer.inner().to_vec(),
+            color: layer.metadata().color.clone(),
+            description: layer.metadata().description.clone(),
+        }
+    }
+}
+
+impl From<BinaryLayer> for Layer {
+    fn from(layer: BinaryLayer) -> Self {
+        let metadata = LayerMetadata {
+            color: layer.color,
+            description: layer.description,
+        };
+
+        Layer::from(layer.keys).with_metadata(metadata)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BinaryGeometry {
+    row_offsets: Vec<f64>,
+    keys: Vec<Vec<(f64, f64)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryBoard {
+    Named(String),
+    Relative(Vec<Vec<(f64, bool)>>),
+    Full(Vec<Vec<(f64, f64, f64, f64)>>),
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryAnchor {
+    Position(Anchor),
+    Char(char),
+}
+
+#[derive(Serialize, Deserialize)]
+enum BinaryFingering {
+    Explicit(BinaryFingers),
+    Implicit(String),
+    None,
+}
+
+/// Fingers of an explicit fingering, along with the notation each row was written in.
+#[derive(Serialize, Deserialize)]
+struct BinaryFingers(Vec<Vec<Finger>>, Vec<RowNotation>);
+
+impl From<&Fingering> for BinaryFingers {
+    fn from(fingering: &Fingering) -> Self {
+        BinaryFingers(
+            fingering.inner().to_vec(),
+            fingering.row_notations().to_vec(),
+        )
+    }
+}
+
+impl From<BinaryFingers> for Fingering {
+    fn from(BinaryFingers(fingers, notations): BinaryFingers) -> Self {
+        Fingering::with_row_notations(fingers, notations)
+    }
+}
+
+impl From<&ParsedFingering> for BinaryFingering {
+    fn from(fingering: &ParsedFingering) -> Self {
+        match fingering {
+            ParsedFingering::Explicit(f) => BinaryFingering::Explicit(f.into()),
+            ParsedFingering::Implicit(n) => BinaryFingering::Implicit(n.to_string()),
+            ParsedFingering::None => BinaryFingering::None,
+        }
+    }
+}
+
+impl From<BinaryFingering> for ParsedFingering {
+    fn from(fingering: BinaryFingering) -> Self {
+        match fingering {
+            BinaryFingering::Explicit(f) => ParsedFingering::Explicit(f.into()),
+            // Parsing a `NamedFingering` is infallible.
+            BinaryFingering::Implicit(n) => {
+                ParsedFingering::Implicit(n.parse::<NamedFingering>().unwrap())
+            }
+            BinaryFingering::None => ParsedFingering::None,
+        }
+    }
+}
+
+impl From<&DofIntermediate> for BinaryDof {
+    fn from(inter: &DofIntermediate) -> Self {
+        let board = match &inter.board {
+            ParseKeyboard::Named(n) => BinaryBoard::Named(n.to_string()),
+            ParseKeyboard::Relative(r) => BinaryBoard::Relative(
+                r.rows()
+                    .map(|r| r.iter().map(|k| (k.width, k.has_key)).collect())
+                    .collect(),
+            ),
+            ParseKeyboard::Full(f) => BinaryBoard::Full(
+                f.rows()
+                    .map(|r| {
+                        r.iter()
+                            .map(|k| (k.x(), k.y(), k.width(), k.height()))
+                            .collect()
+                    })
+                    .collect(),
+            ),
+        };
+
+        Self {
+            name: inter.name.clone(),
+            version: inter.version.clone(),
+            authors: inter.authors.clone(),
+            board,
+            year: inter.year,
+            description: inter.description.clone(),
+            languages: inter.languages.clone(),
+            link: inter.link.clone(),
+            tags: inter.tags.clone(),
+            corpus: inter.corpus.clone(),
+            layers: inter
+                .layers
+                .iter()
+                .map(|(name, l)| (name.clone(), l.into()))
+                .collect(),
+            base_layer: inter.base_layer.clone(),
+            anchor: inter.anchor.map(|a| match a {
+                ParsedAnchor::Position(a) => BinaryAnchor::Position(a),
+                ParsedAnchor::Char(c) => BinaryAnchor::Char(c),
+            }),
+            geometry: inter.geometry.as_ref().map(|g| BinaryGeometry {
+                row_offsets: g.row_offsets().to_vec(),
+                keys: g.keys().to_vec(),
+            }),
+            key_ids: inter.key_ids.clone(),
+            thumbs: inter.thumbs.as_ref().map(Into::into),
+            thumb_fingering: inter.thumb_fingering.as_ref().map(Into::into),
+            auto_shift: inter.auto_shift,
+            case_insensitive_layers: inter.case_insensitive_layers,
+            fingering: inter.fingering.as_ref().map(Into::into),
+            fingerings: inter
+                .fingerings
+                .as_ref()
+                .map(|f| f.iter().map(|(name, f)| (name.clone(), f.into())).collect()),
+            locked: inter.locked.clone(),
+            checksum: inter.checksum.clone(),
+        }
+    }
+}
+
+impl From<BinaryDof> for DofIntermediate {
+    fn from(bin: BinaryDof) -> Self {
+        let board = match bin.board {
+            // Parsing a `KeyboardType` is infallible.
+            BinaryBoard::Named(n) => ParseKeyboard::Named(n.parse::<KeyboardType>().unwrap()),
+            BinaryBoard::Relative(r) => ParseKeyboard::Relative(
+                r.into_iter()
+                    .map(|r| {
+                        r.into_iter()
+                            .map(|(width, has_key)| RelativeKey { width, has_key })
+                            .collect()
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            BinaryBoard::Full(f) => ParseKeyboard::Full(
+                f.into_iter()
+                    .map(|r| {
+                        r.into_iter()
+                            .map(|(x, y, w, h)| PhysicalKey::xywh(x, y, w, h))
+                            .collect()
+                    })
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        };
+
+        Self {
+            name: bin.name,
+            version: bin.version,
+            authors: bin.authors,
+            board,
+            year: bin.year,
+            description: bin.description,
+            languages: bin.languages,
+            link: bin.link,
+            tags: bin.tags,
+            corpus: bin.corpus,
+            layers: bin
+                .layers
+                .into_iter()
+                .map(|(name, l)| (name, l.into()))
+                .collect(),
+            base_layer: bin.base_layer,
+            anchor: bin.anchor.map(|a| match a {
+                BinaryAnchor::Position(a) => ParsedAnchor::Position(a),
+                BinaryAnchor::Char(c) => ParsedAnchor::Char(c),
+            }),
+            geometry: bin.geometry.map(|g| Geometry::new(g.row_offsets, g.keys)),
+            key_ids: bin.key_ids,
+            thumbs: bin.thumbs.map(Into::into),
+            thumb_fingering: bin.thumb_fingering.map(Into::into),
+            auto_shift: bin.auto_shift,
+            case_insensitive_layers: bin.case_insensitive_layers,
+            fingering: bin.fingering.map(Into::into),
+            fingerings: bin
+                .fingerings
+                .map(|f| f.into_iter().map(|(name, f)| (name, f.into())).collect()),
+            locked: bin.locked,
+            checksum: bin.checksum,
+        }
+    }
+}
+
+impl DofIntermediate {
+    /// Serialize into a compact binary format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        postcard::to_allocvec(&BinaryDof::from(self)).map_err(Into::into)
+    }
+
+    /// Deserialize from the binary format produced by
+    /// [`DofIntermediate::to_bytes()`](crate::DofIntermediate::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        postcard::from_bytes::<BinaryDof>(bytes)
+            .map(Into::into)
+            .map_err(Into::into)
+    }
+}
+
+impl Dof {
+    /// Serialize into a compact binary format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        DofIntermediate::from(self.clone()).to_bytes()
+    }
+
+    /// Deserialize from the binary format produced by [`Dof::to_bytes()`](crate::Dof::to_bytes),
+    /// validating the result like parsing a .dof would.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        DofIntermediate::from_bytes(bytes)?.try_into()
+    }
+}
+
+impl From<postcard::Error> for DofError {
+    fn from(value: postcard::Error) -> Self {
+        DErr::Binary(value.to_string()).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(json: &str) {
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+        let bytes = inter.to_bytes().expect("couldn't serialize to bytes");
+
+        assert!(bytes.len() < json.len());
+        assert_eq!(DofIntermediate::from_bytes(&bytes), Ok(inter));
+
+        let dof = serde_json::from_str::<Dof>(json).expect("couldn't parse json");
+        let bytes = dof.to_bytes().expect("couldn't serialize to bytes");
+
+        assert_eq!(Dof::from_bytes(&bytes), Ok(dof));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        round_trip(include_str!("../example_dofs/minimal_valid.dof"));
+        round_trip(include_str!("../example_dofs/aptmak.dof"));
+        round_trip(include_str!("../example_dofs/buggy.dof"));
+        round_trip(include_str!("../example_dofs/maximal.dof"));
+        round_trip(include_str!("../example_dofs/iso.dof"));
+    }
+
+    #[test]
+    fn binary_garbage() {
+        assert!(DofIntermediate::from_bytes(&[0xff, 0xff, 0xff]).is_err());
+    }
+}
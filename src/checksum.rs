@@ -0,0 +1,202 @@
+//! Contains methods to compute and verify a checksum of a layout using
+//! [`sha2`](https://crates.io/crates/sha2), to detect .dof files that were corrupted or tampered with.
+//! Requires the `checksum` feature.
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{DofErrorInner as DErr, DofIntermediate, Result};
+
+impl DofIntermediate {
+    /// Compute the SHA-256 checksum of the layout as a lowercase hex string. It is computed over the
+    /// layout serialized to JSON with every object's keys sorted, leaving out the `checksum` field itself
+    /// and the `corpus` field, which describes how the layout was analyzed rather than its content. This
+    /// way the checksum doesn't depend on the order fields or layers were written in.
+    pub fn compute_checksum(&self) -> String {
+        let mut content = self.clone();
+        content.checksum = None;
+        content.corpus = None;
+
+        // A `DofIntermediate` can always be represented as a json value.
+        let value = sorted(serde_json::to_value(&content).unwrap());
+
+        Sha256::digest(value.to_string())
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect()
+    }
+
+    /// Check if the `checksum` of the layout matches the checksum of its content, ignoring case. Returns a
+    /// `NoChecksum` error if the layout doesn't have a checksum.
+    pub fn verify_checksum(&self) -> Result<bool> {
+        match &self.checksum {
+            Some(checksum) => Ok(checksum.eq_ignore_ascii_case(&self.compute_checksum())),
+            None => Err(DErr::NoChecksum.into()),
+        }
+    }
+}
+
+/// Sort the keys of every object in a json value.
+fn sorted(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+
+            Value::Object(entries.into_iter().map(|(k, v)| (k, sorted(v))).collect())
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sorted).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dofinitions::{Key, KeyboardType},
+        Dof,
+    };
+
+    #[test]
+    fn checksum() {
+        let json = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+        assert_eq!(inter.verify_checksum(), Err(DErr::NoChecksum.into()));
+
+        let checksum = inter.compute_checksum();
+        assert_eq!(checksum.len(), 64);
+
+        inter.checksum = Some(checksum.to_uppercase());
+        assert_eq!(inter.compute_checksum(), checksum);
+        assert_eq!(inter.verify_checksum(), Ok(true));
+        assert!(Dof::try_from(inter.clone()).is_ok());
+
+        inter.corpus = Some("english-10k".into());
+        assert_eq!(inter.verify_checksum(), Ok(true));
+
+        inter.name = "Tampered".into();
+        assert_eq!(inter.verify_checksum(), Ok(false));
+        assert_eq!(
+            Dof::try_from(inter).expect_err("checksum doesn't match"),
+            DErr::ChecksumMismatch.into()
+        );
+    }
+
+    #[test]
+    fn checksum_round_trip() {
+        let json = r#"{
+            "name": "Normalized",
+            "board": "ansi",
+            "tags": ["Split", "split"],
+            "anchor": "q",
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p",
+                    "a s d f g  h j k l ;",
+                    "z x c v b  n m , . /"
+                ]
+            },
+            "fingering": "traditional"
+        }"#;
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+        inter.checksum = Some(inter.compute_checksum());
+
+        let dof = Dof::try_from(inter).expect("checksum should match");
+        let serialized = serde_json::to_string(&dof).expect("couldn't serialize");
+        let reparsed =
+            serde_json::from_str::<Dof>(&serialized).expect("checksum should still match");
+
+        assert_eq!(reparsed.layers(), dof.layers());
+        assert_eq!(reparsed.tags(), ["split"]);
+        assert_eq!(
+            DofIntermediate::from(reparsed),
+            DofIntermediate::from(dof.clone())
+        );
+        assert!(Dof::try_from(DofIntermediate::from(dof)).is_ok());
+    }
+
+    fn checksummed() -> DofIntermediate {
+        let json = r#"{
+            "name": "Checksummed",
+            "board": "ansi",
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p [ ] \\",
+                    "a s d f g  h j k l ; '",
+                    "z x c v b  n m , . /"
+                ],
+                "shift": [
+                    "Q W E R T  Y U I O P { } |",
+                    "A S D F G  H J K L : \"",
+                    "Z X C V B  N M < > ?"
+                ]
+            },
+            "fingering": "traditional"
+        }"#;
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+        inter.checksum = Some(inter.compute_checksum());
+
+        assert!(Dof::try_from(inter.clone()).is_ok());
+
+        inter
+    }
+
+    #[test]
+    fn checksum_from_template() {
+        let template = checksummed();
+        let src = r#"{ "name": "Derived", "fingering": "angle" }"#;
+
+        let derived = DofIntermediate::from_str_with_template(src, &template)
+            .expect("couldn't apply template");
+
+        assert_ne!(derived.checksum, template.checksum);
+        assert!(Dof::try_from(derived).is_ok());
+    }
+
+    #[test]
+    fn checksum_minimal_json() {
+        let minimal = checksummed().to_minimal_json().expect("couldn't serialize");
+
+        assert!(serde_json::from_str::<Dof>(&minimal).is_ok());
+    }
+
+    #[test]
+    fn checksum_iso_conversion() {
+        let iso = checksummed().to_iso().expect("couldn't convert to iso");
+        assert!(Dof::try_from(iso.clone()).is_ok());
+
+        let ansi = iso.to_ansi().expect("couldn't convert back to ansi");
+        assert!(Dof::try_from(ansi).is_ok());
+    }
+
+    #[test]
+    fn checksum_remap_order() {
+        let inter = checksummed();
+        let mapping = [13, 11, 10]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(row, len)| (0..len).map(move |col| (row, len - 1 - col)))
+            .collect::<Vec<_>>();
+
+        let remapped = inter
+            .remap_order(KeyboardType::Ansi, KeyboardType::Ansi, &mapping)
+            .expect("couldn't remap");
+
+        assert!(Dof::try_from(remapped).is_ok());
+    }
+
+    #[test]
+    fn checksum_pad_layers() {
+        let mut inter = checksummed();
+        inter.layers.get_mut("shift").unwrap().0[2].pop();
+        inter.checksum = Some(inter.compute_checksum());
+
+        inter.pad_layers(Key::Transparent);
+
+        assert!(Dof::try_from(inter).is_ok());
+    }
+}
@@ -6,8 +6,9 @@ use crate::{Anchor, DofError, DofErrorInner, Fingering, Keyboard, Result};
 
 /// Represents a finger. Implements `ToString` and `FromStr`, where each finger can either be represented
 /// in string form as `LP`, `LR` (left pinky, left ring) or as a number where `LP`= 0, `LR`= 1 up to
-/// `RP`= 9
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// `RP`= 9. Keys that aren't pressed by any finger use `Unassigned`, represented as `-` or `x`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "binary", derive(serde::Serialize, serde::Deserialize))]
 pub enum Finger {
     /// Left Pinky
     LP,
@@ -29,6 +30,20 @@ pub enum Finger {
     RR,
     /// Right Pinky
     RP,
+    /// No finger, meaning the key can't be typed. Useful while a fingering is still incomplete.
+    Unassigned,
+}
+
+/// Notation the fingers of a fingering are written in, as returned by
+/// [`fingering_notation()`](crate::DofIntermediate::fingering_notation).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FingerNotation {
+    /// Fingers are written with letters, like `LP` or `ri`.
+    Letters,
+    /// Fingers are written as numbers, like `0` or `6`.
+    Numeric,
+    /// The fingering is given by name, like `angle`, rather than per key.
+    Named,
 }
 
 /// Enum to specify both hands. Used in combination with [`Finger`](crate::dofinitions::Finger).
@@ -80,30 +95,58 @@ impl Finger {
         matches!(self, Self::LT | Self::RT)
     }
 
-    /// Returns which `Hand` the finger is on.
-    pub const fn hand(&self) -> Hand {
+    /// Returns which `Hand` the finger is on, or `None` for `Finger::Unassigned`.
+    pub const fn hand(&self) -> Option<Hand> {
         use Finger::*;
 
         match self {
-            LP | LR | LM | LI | LT => Hand::Left,
-            RP | RR | RM | RI | RT => Hand::Right,
+            LP | LR | LM | LI | LT => Some(Hand::Left),
+            RP | RR | RM | RI | RT => Some(Hand::Right),
+            Unassigned => None,
         }
     }
 
     /// Checks if the finger is on the left hand (includes thumb)
     pub const fn is_on_left_hand(&self) -> bool {
-        matches!(self.hand(), Hand::Left)
+        matches!(self.hand(), Some(Hand::Left))
     }
 
     /// Checks if the finger is on the right hand (includes thumb)
     pub const fn is_on_right_hand(&self) -> bool {
-        matches!(self.hand(), Hand::Right)
+        matches!(self.hand(), Some(Hand::Right))
+    }
+
+    /// Checks if the finger is `Finger::Unassigned`, meaning the key can't be typed.
+    pub const fn is_unassigned(&self) -> bool {
+        matches!(self, Self::Unassigned)
+    }
+
+    /// Get the same finger on the other hand, like `RP` for `LP`. `Finger::Unassigned` stays unassigned.
+    pub const fn mirrored(&self) -> Self {
+        use Finger::*;
+
+        match self {
+            LP => RP,
+            LR => RR,
+            LM => RM,
+            LI => RI,
+            LT => RT,
+            RT => LT,
+            RI => LI,
+            RM => LM,
+            RR => LR,
+            RP => LP,
+            Unassigned => Unassigned,
+        }
     }
 }
 
 impl Display for Finger {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        match self {
+            Self::Unassigned => write!(f, "-"),
+            _ => write!(f, "{self:?}"),
+        }
     }
 }
 
@@ -125,14 +168,16 @@ impl FromStr for Finger {
             "rm" | "RM" | "7" => Ok(RM),
             "rr" | "RR" | "8" => Ok(RR),
             "rp" | "RP" | "9" => Ok(RP),
+            "-" | "x" | "X" => Ok(Unassigned),
             _ => Err(DofErrorInner::FingerParseError(s.to_string()).into()),
         }
     }
 }
 
-/// Represents known fingerings with names. Currently these are `Traditional` and `Angle`. A `Custom` type
-/// is also specified, though this isn't particularly useful in use with the rest of the library. `FromStr`
-/// uses `standard` and `traditional` for `Traditional`, and `angle` for `Angle`.
+/// Represents known fingerings with names. Currently these are `Traditional`, `Angle`, `Wide` and `AngleWide`.
+/// A `Custom` type is also specified, though this isn't particularly useful in use with the rest of the
+/// library. `FromStr` uses `standard` and `traditional` for `Traditional`, `angle` for `Angle`, `wide` for
+/// `Wide` and `angle-wide` for `AngleWide`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum NamedFingering {
     /// Traditional fingering. Default value.
@@ -140,6 +185,11 @@ pub enum NamedFingering {
     Traditional,
     /// Fingering for angle mod
     Angle,
+    /// Fingering for wide mod, where the right hand is moved one column to the right so the right index
+    /// finger also covers the column that's normally pressed by the right middle finger.
+    Wide,
+    /// Fingering for angle mod combined with wide mod
+    AngleWide,
     /// Any custom type of fingering. This is technically valid in a .dof, but not supported to be worked with.
     Custom(String),
 }
@@ -149,6 +199,8 @@ impl Display for NamedFingering {
         let s = match self {
             Self::Traditional => "traditional",
             Self::Angle => "angle",
+            Self::Wide => "wide",
+            Self::AngleWide => "angle-wide",
             Self::Custom(name) => name.as_str(),
         };
 
@@ -163,6 +215,8 @@ impl FromStr for NamedFingering {
         let res = match s.to_lowercase().as_str() {
             "standard" | "traditional" => Self::Traditional,
             "angle" => Self::Angle,
+            "wide" => Self::Wide,
+            "angle-wide" | "anglewide" | "angle_wide" | "wide-angle" => Self::AngleWide,
             name => Self::Custom(name.into()),
         };
 
@@ -170,10 +224,45 @@ impl FromStr for NamedFingering {
     }
 }
 
+impl NamedFingering {
+    /// Names of all known fingerings as they are written in a .dof file.
+    pub const KNOWN: [&'static str; 5] = ["traditional", "standard", "angle", "wide", "angle-wide"];
+
+    /// Get the known fingering name that is closest to the provided name, measured in edit distance.
+    pub fn closest_known(name: &str) -> &'static str {
+        let name = name.to_lowercase();
+
+        Self::KNOWN
+            .into_iter()
+            .min_by_key(|known| edit_distance(&name, known))
+            .unwrap()
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + (ca != *cb) as usize;
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+
+        prev = cur;
+    }
+
+    prev[b.len()]
+}
+
 /// Covers a wide range of keys that don't necessarily output characters, but are still commonly found on a
 /// keyboard. Shift is meant to function the same as a `Key::Layer { layer: "shift" }` key.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "binary", derive(serde::Serialize, serde::Deserialize))]
 pub enum SpecialKey {
     Esc,
     Repeat,
@@ -189,6 +278,26 @@ pub enum SpecialKey {
     Fn,
     Backspace,
     Del,
+    /// The tall enter key of iso boards, which spans the top two letter rows.
+    IsoEnter,
+}
+
+impl SpecialKey {
+    /// Get the bytes a terminal receives when the key is pressed, like `\x1b[3~` for `Del`. Returns `None`
+    /// for keys that don't send anything on their own, like modifiers and `Repeat`.
+    pub const fn ansi_sequence(&self) -> Option<&'static [u8]> {
+        use SpecialKey::*;
+
+        match self {
+            Esc => Some(b"\x1b"),
+            Space => Some(b" "),
+            Tab => Some(b"\t"),
+            Enter | IsoEnter => Some(b"\r"),
+            Backspace => Some(b"\x7f"),
+            Del => Some(b"\x1b[3~"),
+            Repeat | Shift | Caps | Ctrl | Alt | Meta | Menu | Fn => None,
+        }
+    }
 }
 
 /// Covers all keys commonly found on a keyboard. Implements `ToString` and `FromStr`, where the latter has
@@ -201,15 +310,29 @@ pub enum SpecialKey {
 ///     - `Key::Special(SpecialKey::Enter)` when it's equal to `\n`,
 ///     - `Key::Special(SpecialKey::Tab)` when it's equal to `\t`,
 ///     - `Key::Char` otherwise.
+/// * if the length is 2 and it starts with a backslash, output `Key::Char` with the character after the
+///   backslash. This means `\\~` and `\\*` become `Key::Char('~')` and `Key::Char('*')`, and `\\\\`
+///   becomes `Key::Char('\\')`,
 /// * if the length is more than 1, outputs
-///     - `Key::Char('~')` and `Key::Char('*')` if they contain `\\~` and `\\*` respectively,
 ///     - `Key::Special` based on their names in the readme. You can also check the `FromStr`
-///        implementation itself,
+///       implementation itself,
 ///     - `Key::Layer` if it leads with an `@`.
+///     - `Key::Marker` with the leading `//` removed if it leads with `//`, like `//thumbs`,
 ///     - `Key::Word` with its first character removed if it starts with `#`, `\\#` or`\\@`,
 ///     - `Key::Word` otherwise.
+/// * if it's of the form `tap(a)hold(ctl)`, output `Key::TapHold`, where `a` and `ctl` are parsed as keys
+///   of their own. This takes precedence over all of the above.
+/// * if it's wrapped in double quotes, like `"Caps Lock"`, output `Key::Word` with the quotes removed.
+///   This also takes precedence over everything but `tap(a)hold(ctl)`, and allows words containing
+///   whitespace. Inside the quotes, a backslash escapes a following quote, backslash or whitespace, so
+///   `"a\" b"` becomes `Key::Word("a\" b")` and `"\ a"` becomes `Key::Word(" a")`.
+///
+/// Keys in a row are separated by whitespace unless they're quoted, so a backslash can't escape a space.
+/// The row `"\\ a"` in a .dof file, which is the text `\ a` once the json is parsed, becomes the keys
+/// `Key::Char('\\')` and `Key::Char('a')`: the lone backslash is parsed like any other single character.
 #[allow(missing_docs)]
-#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "binary", derive(serde::Serialize, serde::Deserialize))]
 pub enum Key {
     #[default]
     Empty,
@@ -220,11 +343,29 @@ pub enum Key {
     Layer {
         name: String,
     },
+    /// Key that outputs `tap` when tapped and `hold` when held, like a home row modifier.
+    TapHold {
+        tap: Box<Key>,
+        hold: Box<Key>,
+    },
+    /// Visual annotation in a row that isn't a real key. It takes up a position like any other key, but
+    /// doesn't output anything and is ignored in analysis.
+    Marker(String),
 }
 
 impl Key {
     /// Turns lowercase characters into their qwerty shift output, and turns `Special`` keys `Transparent`.
     pub fn shifted(&self) -> Self {
+        self.shifted_with(|c| c.to_uppercase().to_string())
+    }
+
+    /// Works like [`shifted()`](crate::dofinitions::Key::shifted), but uses `casing` to uppercase characters
+    /// that aren't qwerty symbols. This allows for locale-aware casing, like mapping `i` to `İ` for Turkish.
+    /// If `casing` returns more than one character, the key becomes a `Word`.
+    pub fn shifted_with<F>(&self, casing: F) -> Self
+    where
+        F: Fn(char) -> String,
+    {
         use Key::*;
 
         match self {
@@ -250,16 +391,20 @@ impl Key {
                 '=' => Char('+'),
                 '-' => Char('_'),
                 '\\' => Char('|'),
-                c => {
-                    let mut upper = c.to_uppercase();
-                    if upper.clone().count() == 1 {
-                        Char(upper.next().unwrap())
-                    } else {
-                        Word(upper.to_string())
+                &c => {
+                    let upper = casing(c);
+                    let mut chars = upper.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(u), None) => Char(u),
+                        _ => Word(upper),
                     }
                 }
             },
             Special(_) => Transparent,
+            TapHold { tap, hold } => TapHold {
+                tap: Box::new(tap.shifted_with(casing)),
+                hold: hold.clone(),
+            },
             k => k.clone(),
         }
     }
@@ -288,12 +433,59 @@ impl Key {
         matches!(self, Key::Transparent)
     }
 
+    /// Check if the key is of type [`Key::Special`](crate::dofinitions::Key::Special) which holds a
+    /// [`SpecialKey`](crate::dofinitions::SpecialKey).
+    pub const fn is_special(&self) -> bool {
+        matches!(self, Key::Special(_))
+    }
+
     /// Check if the key is of type [`Key::Layer`](crate::dofinitions::Key::Layer) which holds the name.
     /// of a layer on the layout
     pub const fn is_layer(&self) -> bool {
         matches!(self, Key::Layer { name: _ })
     }
 
+    /// Check if the key is of type [`Key::Marker`](crate::dofinitions::Key::Marker) which annotates a row
+    /// without being a real key.
+    pub const fn is_marker(&self) -> bool {
+        matches!(self, Key::Marker(_))
+    }
+
+    /// Check if the key is of type [`Key::TapHold`](crate::dofinitions::Key::TapHold) which does
+    /// something different when tapped than when held.
+    pub const fn is_tap_hold(&self) -> bool {
+        matches!(self, Key::TapHold { .. })
+    }
+
+    /// Get the key that is output when tapping this key. This is the `tap` key of a
+    /// [`Key::TapHold`](crate::dofinitions::Key::TapHold), and the key itself otherwise.
+    pub fn tap(&self) -> &Key {
+        match self {
+            Key::TapHold { tap, .. } => tap.tap(),
+            k => k,
+        }
+    }
+
+    /// Get the key that is output when holding this key if it is of type
+    /// [`Key::TapHold`](crate::dofinitions::Key::TapHold).
+    pub fn hold(&self) -> Option<&Key> {
+        match self {
+            Key::TapHold { hold, .. } => Some(hold),
+            _ => None,
+        }
+    }
+
+    /// Get the name of the layer this key switches to, either because it is of type
+    /// [`Key::Layer`](crate::dofinitions::Key::Layer), or because it is a
+    /// [`Key::TapHold`](crate::dofinitions::Key::TapHold) that holds a layer key.
+    pub fn layer_target(&self) -> Option<&str> {
+        match self {
+            Key::Layer { name } => Some(name),
+            Key::TapHold { hold, .. } => hold.layer_target(),
+            _ => None,
+        }
+    }
+
     /// Get the output if the key is of type [`Key::Char`](crate::dofinitions::Key::Char).
     pub const fn char_output(&self) -> Option<char> {
         match self {
@@ -331,7 +523,14 @@ impl Display for Key {
                 n @ ('~' | '*') => format!("\\{n}"),
                 n => String::from(*n),
             },
-            Word(w) => w.clone(),
+            // Words containing whitespace are quoted so they stay a single key, and words that would be
+            // parsed as a different key, or start a quoted key, are escaped with a leading `#`.
+            Word(w) if w.contains(char::is_whitespace) => format!("\"{}\"", escape_quoted(w)),
+            Word(w) if w.starts_with('"') => format!("#{w}"),
+            Word(w) => match Key::from(w) {
+                Word(parsed) if &parsed == w => w.clone(),
+                _ => format!("#{w}"),
+            },
             Special(s) => match s {
                 Esc => "esc".into(),
                 Repeat => "rpt".into(),
@@ -347,8 +546,11 @@ impl Display for Key {
                 Fn => "fn".into(),
                 Backspace => "bsp".into(),
                 Del => "del".into(),
+                IsoEnter => "iso-enter".into(),
             },
-            Layer { name } => name.clone(),
+            Layer { name } => format!("@{name}"),
+            TapHold { tap, hold } => format!("tap({tap})hold({hold})"),
+            Marker(label) => format!("//{label}"),
         };
 
         write!(f, "{s}")
@@ -373,6 +575,18 @@ where
 
         let s = value.as_ref();
 
+        if let Some(key) = parse_tap_hold(s) {
+            return key;
+        }
+
+        if let Some(word) = s
+            .strip_prefix('"')
+            .and_then(|s| s.strip_suffix('"'))
+            .filter(|w| !w.is_empty())
+        {
+            return Word(unescape_quoted(word));
+        }
+
         match s.chars().count() {
             0 => Empty,
             1 => match s {
@@ -383,14 +597,14 @@ where
                 "\t" => Special(Tab),
                 _ => Char(s.chars().next().unwrap()),
             },
+            2 if s.starts_with('\\') => Char(s.chars().nth(1).unwrap()),
             _ => match s.to_lowercase().as_str() {
-                "\\~" => Char('~'),
-                "\\*" => Char('*'),
                 "esc" => Special(Esc),
                 "repeat" | "rpt" => Special(Repeat),
                 "space" | "spc" => Special(Space),
                 "tab" | "tb" => Special(Tab),
                 "enter" | "return" | "ret" | "ent" | "rt" => Special(Enter),
+                "iso-enter" | "isoenter" => Special(IsoEnter),
                 "shift" | "shft" | "sft" | "st" => Special(Shift),
                 "caps" | "cps" | "cp" => Special(Caps),
                 "ctrl" | "ctl" | "ct" => Special(Ctrl),
@@ -403,6 +617,7 @@ where
                 _ if s.starts_with('@') => Layer {
                     name: s.chars().skip(1).collect(),
                 },
+                _ if s.len() > 2 && s.starts_with("//") => Marker(s.chars().skip(2).collect()),
                 _ if s.starts_with('#') || s.starts_with("\\#") || s.starts_with("\\@") => {
                     Word(s.chars().skip(1).collect())
                 }
@@ -412,6 +627,81 @@ where
     }
 }
 
+/// Parse a key of the form `tap(a)hold(ctl)`.
+fn parse_tap_hold(s: &str) -> Option<Key> {
+    let (tap, hold) = split_tap_hold(s)?;
+
+    Some(Key::TapHold {
+        tap: Box::new(tap.into()),
+        hold: Box::new(hold.into()),
+    })
+}
+
+/// Check if `c` is escaped by a backslash in front of it inside a quoted key.
+pub(crate) fn is_quote_escapable(c: char) -> bool {
+    c == '"' || c == '\\' || c.is_whitespace()
+}
+
+/// Escape a word so it can be wrapped in quotes. Quotes and backslashes are escaped with a backslash, as
+/// is whitespace at the start or end, which would otherwise not be part of the quoted key.
+fn escape_quoted(w: &str) -> String {
+    let last = w.chars().count() - 1;
+
+    w.chars()
+        .enumerate()
+        .fold(String::with_capacity(w.len()), |mut escaped, (i, c)| {
+            if c == '"' || c == '\\' || (c.is_whitespace() && (i == 0 || i == last)) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+}
+
+/// Remove the escaping of a quoted key, where a backslash escapes a following quote, backslash or
+/// whitespace. Any other backslash is kept as is.
+fn unescape_quoted(w: &str) -> String {
+    let mut chars = w.chars().peekable();
+    let mut unescaped = String::with_capacity(w.len());
+
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && is_quote_escapable(next) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+/// Split a key of the form `tap(a)hold(ctl)` into its tap and hold part. The parts are split at the first
+/// `)hold(` with balanced parentheses before it, so tap-holds can be nested like
+/// `tap(tap(a)hold(ctl))hold(alt)`. If there is none, like when the tap part is `(`, the first `)hold(`
+/// is used.
+pub(crate) fn split_tap_hold(s: &str) -> Option<(&str, &str)> {
+    let inner = s.strip_prefix("tap(")?.strip_suffix(')')?;
+
+    let mut depth = 0i32;
+    let balanced = inner.char_indices().find_map(|(i, c)| {
+        if depth == 0 && inner[i..].starts_with(")hold(") {
+            return Some(i);
+        }
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        None
+    });
+
+    let split = balanced.or_else(|| inner.find(")hold("))?;
+
+    Some((&inner[..split], &inner[split + ")hold(".len()..]))
+}
+
 /// Abstraction of `Vec<usize>` where each index represents a row on a layout with a specific amount of keys.
 #[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct Shape(Vec<usize>);
@@ -461,6 +751,10 @@ impl Shape {
 
 /// Some default form factors. Options are Ansi, Iso, Ortho (being 3x10 + 3 thumb keys per thumb), Colstag
 /// (being 3x10 + 3 thumb keys per thumb) and a custom option if any anything but the prior options is provided.
+///
+/// On `Iso` boards, the tall enter key lives at the end of the top letter row, next to qwerty `]`, even
+/// though it spans down into the home row. The extra ISO key lives at the start of the bottom letter row,
+/// between left shift and qwerty `z`.
 #[allow(missing_docs)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum KeyboardType {
@@ -504,6 +798,22 @@ impl KeyboardType {
                 vec![LP, LP, LT, LT, RT, RT, RP, RP],
             ]
             .into(),
+            (Ansi, Wide) => vec![
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP],
+                vec![LP, LP, LT, LT, RT, RT, RP, RP],
+            ]
+            .into(),
+            (Ansi, AngleWide) => vec![
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP],
+                vec![LP, LR, LM, LI, LI, LI, RI, RI, RI, RM, RR, RP],
+                vec![LP, LP, LT, LT, RT, RT, RP, RP],
+            ]
+            .into(),
             (Iso, Traditional) => vec![
                 vec![LP, LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP, RP],
                 vec![LP, LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP, RP],
@@ -520,6 +830,22 @@ impl KeyboardType {
                 vec![LP, LP, LT, LT, RT, RT, RP, RP],
             ]
             .into(),
+            (Iso, Wide) => vec![
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP],
+                vec![LP, LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP],
+                vec![LP, LP, LT, LT, RT, RT, RP, RP],
+            ]
+            .into(),
+            (Iso, AngleWide) => vec![
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, RI, RI, RI, RM, RR, RP, RP],
+                vec![LP, LP, LR, LM, LI, LI, LI, RI, RI, RI, RM, RR, RP],
+                vec![LP, LP, LT, LT, RT, RT, RP, RP],
+            ]
+            .into(),
             (Ortho, Traditional) => vec![
                 vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
                 vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
@@ -534,6 +860,13 @@ impl KeyboardType {
                 vec![LT, LT, LT, RT, RT, RT],
             ]
             .into(),
+            (_, NamedFingering::Custom(name)) => {
+                return Err(DofErrorInner::UnknownFingering(
+                    name.clone(),
+                    NamedFingering::closest_known(name),
+                )
+                .into())
+            }
             (board, &f) => {
                 return Err(DofErrorInner::UnsupportedKeyboardFingeringCombo(
                     board.clone(),
@@ -546,6 +879,15 @@ impl KeyboardType {
         Ok(fingering)
     }
 
+    /// Get the amount of keys on the board, like 61 for a 60% `Ansi` board. Returns `None` for boards
+    /// that don't have a fixed size, being `Ortho`, `Colstag` and `Custom`.
+    pub fn slot_count(&self) -> Option<usize> {
+        match self {
+            Self::Ansi | Self::Iso => Some(self.shape().inner().iter().sum()),
+            _ => None,
+        }
+    }
+
     /// Checks if the keyboard is Custom.
     pub const fn is_custom(&self) -> bool {
         matches!(self, Self::Custom(_))
@@ -565,6 +907,54 @@ impl KeyboardType {
             Custom(_) => Anchor::new(0, 0),
         }
     }
+
+    /// Get the [`Cluster`](crate::dofinitions::Cluster) a position on the physical keyboard belongs to.
+    /// Returns `None` for `Custom` keyboards and for positions that aren't on the keyboard. As none of the
+    /// known keyboards have a navigation cluster or numpad, no position belongs to `Nav` or `Numpad`.
+    pub fn cluster(&self, row: usize, col: usize) -> Option<Cluster> {
+        use Cluster::*;
+        use KeyboardType::*;
+
+        let shape = match self {
+            Custom(_) => return None,
+            board => board.shape(),
+        };
+
+        let row_len = shape.inner().get(row).copied()?;
+        if col >= row_len {
+            return None;
+        }
+
+        let cluster = match (self, row, col) {
+            (Ansi | Iso, 0, c) if c == row_len - 1 => Modifiers,
+            (Ansi | Iso, 0, _) => Numbers,
+            // On ansi the last key of the top letter row is `\`, on iso it's the tall enter key. Iso has
+            // `#` at the end of the home row instead, as the enter key takes up the position of ansi's.
+            (Ansi, 1, 13) | (Iso, 2, 12) => Alphas,
+            (Ansi | Iso, 1..=3, c) if c == 0 || c == row_len - 1 => Modifiers,
+            (Ansi | Iso, 1..=3, _) => Alphas,
+            (Ortho | Colstag, 0..=2, _) => Alphas,
+            _ => Modifiers,
+        };
+
+        Some(cluster)
+    }
+}
+
+/// Physical regions of a keyboard, which can be used to only look at part of a layout. Which positions
+/// belong to which cluster depends on the [`KeyboardType`](crate::dofinitions::KeyboardType).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cluster {
+    /// Letters and the punctuation around them
+    Alphas,
+    /// The number row
+    Numbers,
+    /// Arrow keys and keys like home, end, page up and page down
+    Nav,
+    /// The numpad
+    Numpad,
+    /// Keys around the other clusters like shift, enter and space, as well as thumb keys
+    Modifiers,
 }
 
 impl Display for KeyboardType {
@@ -598,3 +988,253 @@ impl FromStr for KeyboardType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backslash_tokens() {
+        use Key::*;
+
+        assert_eq!(Key::from("\\"), Char('\\'));
+        assert_eq!(Key::from("\\\\"), Char('\\'));
+        assert_eq!(Key::from("\\~"), Char('~'));
+        assert_eq!(Key::from("\\*"), Char('*'));
+        assert_eq!(Key::from("\\#"), Char('#'));
+        assert_eq!(Key::from("\\@"), Char('@'));
+        assert_eq!(Key::from("\\a"), Char('a'));
+        assert_eq!(Key::from("\\#hi"), Word("#hi".into()));
+        assert_eq!(Key::from("\\@altgr"), Word("@altgr".into()));
+        assert_eq!(Key::from("\\abc"), Word("\\abc".into()));
+    }
+
+    #[test]
+    fn backslash_row() {
+        let layer = serde_json::from_str::<crate::Layer>(r#"["a \\ \\\\ \\~ \\", "\\ b"]"#)
+            .expect("couldn't parse layer");
+
+        assert_eq!(
+            layer.inner(),
+            &[
+                vec![
+                    Key::Char('a'),
+                    Key::Char('\\'),
+                    Key::Char('\\'),
+                    Key::Char('~'),
+                    Key::Char('\\'),
+                ],
+                vec![Key::Char('\\'), Key::Char('b')],
+            ]
+        );
+    }
+
+    #[test]
+    fn backslash_display() {
+        for key in [
+            Key::Char('\\'),
+            Key::Char('~'),
+            Key::Char('*'),
+            Key::Char('#'),
+            Key::Word("\\a".into()),
+            Key::Word("#".into()),
+        ] {
+            assert_eq!(Key::from(key.to_string()), key);
+        }
+    }
+
+    #[test]
+    fn tap_hold() {
+        let home_row_mod = Key::from("tap(a)hold(ctrl)");
+        let layer_tap = Key::from("tap(spc)hold(@nav)");
+
+        assert_eq!(
+            home_row_mod,
+            Key::TapHold {
+                tap: Box::new(Key::Char('a')),
+                hold: Box::new(Key::Special(SpecialKey::Ctrl)),
+            }
+        );
+        assert_eq!(home_row_mod.tap(), &Key::Char('a'));
+        assert_eq!(home_row_mod.hold(), Some(&Key::Special(SpecialKey::Ctrl)));
+        assert_eq!(home_row_mod.layer_target(), None);
+        assert_eq!(layer_tap.layer_target(), Some("nav"));
+        assert_eq!(Key::Char('a').tap(), &Key::Char('a'));
+
+        for key in [
+            home_row_mod,
+            layer_tap,
+            Key::from("tap())hold(~)"),
+            Key::Word("tap(a)hold(b)".into()),
+        ] {
+            assert_eq!(Key::from(key.to_string()), key);
+        }
+
+        let nested = Key::from("tap(tap(a)hold(ctl))hold(alt)");
+
+        assert_eq!(
+            nested,
+            Key::TapHold {
+                tap: Box::new(Key::TapHold {
+                    tap: Box::new(Key::Char('a')),
+                    hold: Box::new(Key::Special(SpecialKey::Ctrl)),
+                }),
+                hold: Box::new(Key::Special(SpecialKey::Alt)),
+            }
+        );
+        assert_eq!(nested.to_string(), "tap(tap(a)hold(ctl))hold(alt)");
+
+        let caps = Key::TapHold {
+            tap: Box::new(Key::Word("Caps Lock".into())),
+            hold: Box::new(Key::Special(SpecialKey::Ctrl)),
+        };
+        assert_eq!(caps.to_string(), r#"tap("Caps Lock")hold(ctl)"#);
+
+        let layer = crate::Layer::from(vec![vec![caps, nested, Key::Char('a')]]);
+        let json = serde_json::to_string(&layer).expect("couldn't serialize layer");
+        assert_eq!(
+            serde_json::from_str::<crate::Layer>(&json).expect("couldn't reparse layer"),
+            layer
+        );
+
+        assert_eq!(Key::from("tap(a)"), Key::Word("tap(a)".into()));
+    }
+
+    #[test]
+    fn shifted_with_casing() {
+        use Key::*;
+
+        let turkish = |c: char| match c {
+            'i' => "İ".to_string(),
+            c => c.to_uppercase().to_string(),
+        };
+
+        assert_eq!(Char('i').shifted(), Char('I'));
+        assert_eq!(Char('i').shifted_with(turkish), Char('İ'));
+        assert_eq!(Char('a').shifted_with(turkish), Char('A'));
+        assert_eq!(Char('1').shifted_with(turkish), Char('!'));
+        assert_eq!(Char('ß').shifted(), Word("SS".into()));
+        assert_eq!(Char('ß').shifted_with(|_| "ẞ".into()), Char('ẞ'));
+    }
+
+    #[test]
+    fn quoted_words() {
+        use Key::*;
+
+        let layer =
+            serde_json::from_str::<crate::Layer>(r#"["\"Caps Lock\" a \" \"spc\"", "' \""]"#)
+                .expect("couldn't parse layer");
+
+        assert_eq!(
+            layer.inner(),
+            &[
+                vec![
+                    Word("Caps Lock".into()),
+                    Char('a'),
+                    Char('"'),
+                    Word("spc".into()),
+                ],
+                vec![Char('\''), Char('"')],
+            ]
+        );
+
+        assert_eq!(Word("Caps Lock".into()).to_string(), r#""Caps Lock""#);
+        assert_eq!(Key::from(r#""""#), Word(r#""""#.into()));
+        assert_eq!(Word(r#""!"#.into()).to_string(), r#"#"!"#);
+
+        assert_eq!(Word(r#"a" b"#.into()).to_string(), r#""a\" b""#);
+        assert_eq!(Word(" a".into()).to_string(), r#""\ a""#);
+        assert_eq!(Word("a\\ ".into()).to_string(), r#""a\\\ ""#);
+        assert_eq!(Key::from(r#""C:\dir a""#), Word(r#"C:\dir a"#.into()));
+
+        let escaped =
+            serde_json::from_str::<crate::Layer>(r#"["\"a\\\" b\" \"\\ a\" \"a\\\\\\ \""]"#)
+                .expect("couldn't parse layer");
+        assert_eq!(
+            escaped.inner(),
+            &[vec![
+                Word(r#"a" b"#.into()),
+                Word(" a".into()),
+                Word("a\\ ".into())
+            ]]
+        );
+
+        let json = serde_json::to_string(&layer).expect("couldn't serialize layer");
+        assert_eq!(
+            serde_json::from_str::<crate::Layer>(&json).expect("couldn't reparse layer"),
+            layer
+        );
+    }
+
+    #[test]
+    fn ansi_sequence() {
+        assert_eq!(SpecialKey::Esc.ansi_sequence(), Some(&b"\x1b"[..]));
+        assert_eq!(SpecialKey::Enter.ansi_sequence(), Some(&b"\r"[..]));
+        assert_eq!(SpecialKey::Del.ansi_sequence(), Some(&b"\x1b[3~"[..]));
+        assert_eq!(SpecialKey::Shift.ansi_sequence(), None);
+    }
+
+    #[test]
+    fn marker() {
+        use Key::*;
+
+        assert_eq!(Key::from("//thumbs"), Marker("thumbs".into()));
+        assert_eq!(Key::from("//"), Word("//".into()));
+        assert_eq!(Marker("thumbs".into()).to_string(), "//thumbs");
+        assert_eq!(Word("//x".into()).to_string(), "#//x");
+        assert_eq!(Key::from("#//x"), Word("//x".into()));
+        assert!(Marker("thumbs".into()).is_marker());
+        assert_eq!(Marker("thumbs".into()).char_output(), None);
+
+        let layer = serde_json::from_str::<crate::Layer>(r#"["a //mid b", "c d"]"#)
+            .expect("couldn't parse layer");
+
+        assert_eq!(layer.shape(), Shape::from([3, 2]));
+        assert_eq!(layer.inner()[0][1], Marker("mid".into()));
+    }
+
+    #[test]
+    fn slot_count() {
+        assert_eq!(KeyboardType::Ansi.slot_count(), Some(61));
+        assert_eq!(KeyboardType::Iso.slot_count(), Some(62));
+        assert_eq!(KeyboardType::Ortho.slot_count(), None);
+        assert_eq!(KeyboardType::Custom("split".into()).slot_count(), None);
+    }
+
+    #[test]
+    fn wide_fingerings() {
+        assert_eq!("Angle-Wide".parse(), Ok(NamedFingering::AngleWide));
+        assert_eq!("wide".parse(), Ok(NamedFingering::Wide));
+
+        for board in [KeyboardType::Ansi, KeyboardType::Iso] {
+            for named in [NamedFingering::Wide, NamedFingering::AngleWide] {
+                let fingering = board.fingering(&named).expect("couldn't get fingering");
+                assert_eq!(fingering.shape(), board.shape());
+
+                assert_eq!(named.to_string().parse(), Ok(named));
+            }
+        }
+
+        let wide = KeyboardType::Ansi
+            .fingering(&NamedFingering::AngleWide)
+            .unwrap();
+        assert_eq!(wide.inner()[2][8], Finger::RI);
+        assert_eq!(wide.inner()[3][3], Finger::LI);
+
+        assert!(KeyboardType::Ortho
+            .fingering(&NamedFingering::Wide)
+            .is_err());
+    }
+
+    #[test]
+    fn unknown_fingering_suggestion() {
+        let named = "angel".parse::<NamedFingering>().unwrap();
+
+        assert_eq!(
+            KeyboardType::Ansi.fingering(&named),
+            Err(DofErrorInner::UnknownFingering("angel".into(), "angle").into())
+        );
+        assert_eq!(NamedFingering::closest_known("tradtional"), "traditional");
+        assert_eq!(NamedFingering::closest_known("anglewid"), "angle-wide");
+    }
+}
@@ -0,0 +1,52 @@
+//! Contains functions to read and write gzip-compressed .dof files using
+//! [`flate2`](https://crates.io/crates/flate2). Requires the `gzip` feature.
+
+use std::io::{Read, Write};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::{DofErrorInner as DErr, DofIntermediate, Result};
+
+/// Decompress a gzip-compressed .dof file from a reader and parse it.
+pub fn from_gz_reader<R: Read>(reader: R) -> Result<DofIntermediate> {
+    let mut json = String::new();
+
+    GzDecoder::new(reader)
+        .read_to_string(&mut json)
+        .map_err(|e| DErr::Gzip(e.to_string()))?;
+
+    serde_json::from_str(&json).map_err(Into::into)
+}
+
+/// Serialize a layout to json and write it to a writer, compressed with gzip.
+pub fn to_gz_writer<W: Write>(inter: &DofIntermediate, writer: W) -> Result<()> {
+    let mut encoder = GzEncoder::new(writer, Compression::default());
+
+    serde_json::to_writer(&mut encoder, inter)?;
+    encoder.finish().map_err(|e| DErr::Gzip(e.to_string()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gzip_round_trip() {
+        let json = include_str!("../example_dofs/maximal.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+        let mut bytes = Vec::new();
+        to_gz_writer(&inter, &mut bytes).expect("couldn't write gzip");
+
+        assert!(bytes.len() < json.len());
+        assert_eq!(from_gz_reader(bytes.as_slice()), Ok(inter));
+    }
+
+    #[test]
+    fn gzip_garbage() {
+        let err = from_gz_reader([0xff, 0xff, 0xff].as_slice()).expect_err("not gzip");
+        assert!(matches!(err.0.as_ref(), DErr::Gzip(_)));
+    }
+}
@@ -1,8 +1,11 @@
 //! Contains some structs and functions that are used when interacting with the layout, like swapping two keys.
 
+use std::collections::{BTreeSet, VecDeque};
+
 use crate::{
-    dofinitions::{Finger, Key},
-    Dof, DofErrorInner as DE, Result,
+    dofinitions::{Cluster, Finger, Key, KeyboardType, SpecialKey},
+    keyboard::ParseKeyboard,
+    Dof, DofErrorInner as DE, DofIntermediate, Keyboard, Layer, ParsedFingering, Result,
 };
 
 /// Represents a (row, column) position on a keyboard. Can be created by `(num, num).into()`.
@@ -142,16 +145,16 @@ impl Dof {
             let char1 = layer
                 .0
                 .get(pos1.row)
-                .ok_or(DE::InvalidPosition(pos1.row as u8, pos1.col as u8))?
+                .ok_or(DE::InvalidPosition(pos1.row, pos1.col))?
                 .get(pos1.col)
-                .ok_or(DE::InvalidPosition(pos1.row as u8, pos1.col as u8))?;
+                .ok_or(DE::InvalidPosition(pos1.row, pos1.col))?;
 
             let char2 = layer
                 .0
                 .get(pos2.row)
-                .ok_or(DE::InvalidPosition(pos2.row as u8, pos2.col as u8))?
+                .ok_or(DE::InvalidPosition(pos2.row, pos2.col))?
                 .get(pos2.col)
-                .ok_or(DE::InvalidPosition(pos2.row as u8, pos2.col as u8))?;
+                .ok_or(DE::InvalidPosition(pos2.row, pos2.col))?;
 
             let char1 = char1 as *const _ as *mut Key;
             let char2 = char2 as *const _ as *mut Key;
@@ -175,16 +178,16 @@ impl Dof {
             let char1 = layer1
                 .0
                 .get_mut(pos1.row)
-                .ok_or(DE::InvalidPosition(pos1.row as u8, pos1.col as u8))?
+                .ok_or(DE::InvalidPosition(pos1.row, pos1.col))?
                 .get_mut(pos1.col)
-                .ok_or(DE::InvalidPosition(pos1.row as u8, pos1.col as u8))?;
+                .ok_or(DE::InvalidPosition(pos1.row, pos1.col))?;
 
             let char2 = layer2
                 .0
                 .get_mut(pos2.row)
-                .ok_or(DE::InvalidPosition(pos2.row as u8, pos2.col as u8))?
+                .ok_or(DE::InvalidPosition(pos2.row, pos2.col))?
                 .get_mut(pos2.col)
-                .ok_or(DE::InvalidPosition(pos2.row as u8, pos2.col as u8))?;
+                .ok_or(DE::InvalidPosition(pos2.row, pos2.col))?;
 
             std::mem::swap(char1, char2);
 
@@ -196,6 +199,425 @@ impl Dof {
     }
 }
 
+impl DofIntermediate {
+    /// Pair up every key on a layer with the finger it is pressed with. Each row of the layer is
+    /// zipped with the matching row of the fingering, meaning that if either is longer than the
+    /// other the overhang is silently dropped. Useful when working with a fingering that is only
+    /// partially specified. Returns an error if the layer doesn't exist or if the fingering can't
+    /// be generated for the board.
+    pub fn key_finger_pairs_lossy<'a>(
+        &'a self,
+        layer: &str,
+    ) -> Result<impl Iterator<Item = (&'a Key, &'a Finger)> + 'a> {
+        let keys = self.layer_or_err(layer)?;
+
+        // An explicit fingering is borrowed, while an implicit one is generated here, so its fingers are
+        // handed out as `'static` references instead. Only one of the two is ever present.
+        let (explicit, generated) = match self.fingering_for(layer)? {
+            ParsedFingering::Explicit(f) => (Some(f), None),
+            _ => (None, Some(self.unshaped_fingering_for(layer)?)),
+        };
+
+        let explicit = explicit.into_iter().flat_map(move |f| {
+            keys.rows()
+                .zip(f.rows())
+                .flat_map(|(key_row, finger_row)| key_row.iter().zip(finger_row))
+        });
+
+        let generated = generated.into_iter().flat_map(move |f| {
+            keys.rows()
+                .zip(f.into_inner())
+                .flat_map(|(key_row, finger_row)| {
+                    let fingers = finger_row
+                        .into_iter()
+                        .map(|f| -> &'a Finger { static_finger(f) });
+                    key_row.iter().zip(fingers)
+                })
+        });
+
+        Ok(explicit.chain(generated))
+    }
+
+    /// Find layers with identical content and keep only one of each, where the base layer and `shift`
+    /// are always kept over other layers and otherwise the first layer in the
+    /// [`LayerMap`](crate::LayerMap) is. Any
+    /// [`Key::Layer`](crate::dofinitions::Key::Layer) pointing to a removed layer is rewritten to
+    /// point to the kept one instead. Returns `(removed, kept)` pairs of layer names.
+    pub fn dedup_layers(&mut self) -> Vec<(String, String)> {
+        let base = self.base_layer_name().map(str::to_owned);
+
+        let mut names = self.layers.keys().cloned().collect::<Vec<_>>();
+        names.sort_by_key(|name| !(Some(name) == base.as_ref() || name == "shift"));
+
+        let mut kept: Vec<String> = Vec::new();
+        let mut renames = Vec::new();
+
+        for name in names {
+            match kept.iter().find(|k| self.layers[*k] == self.layers[&name]) {
+                Some(k) => renames.push((name, k.clone())),
+                None => kept.push(name),
+            }
+        }
+
+        for (removed, _) in &renames {
+            self.layers.remove(removed);
+        }
+
+        for key in self
+            .layers
+            .values_mut()
+            .flat_map(|l| l.0.iter_mut().flatten())
+        {
+            let key = match key {
+                Key::TapHold { hold, .. } => hold.as_mut(),
+                key => key,
+            };
+
+            if let Key::Layer { name } = key {
+                if let Some((_, k)) = renames.iter().find(|(removed, _)| removed == name) {
+                    *name = k.clone();
+                }
+            }
+        }
+
+        renames
+    }
+
+    /// Get the finger a character on a layer is pressed with. Returns `Ok(None)` if the character isn't on
+    /// the layer, and an error if the layer doesn't exist or its fingering can't be determined. If a
+    /// character occurs more than once, the finger of the first occurrence is returned.
+    pub fn finger_for_char(&self, layer: &str, c: char) -> Result<Option<Finger>> {
+        let index = self.char_fingers(layer)?;
+
+        Ok(index.get(&c).map(|&(_, finger)| finger))
+    }
+
+    /// Get only the keys of a layer that are in a specific [`Cluster`](crate::dofinitions::Cluster) of
+    /// the board, like the alphas or the number row. Rows are kept in order with the keys outside of the
+    /// cluster removed, and rows without any keys in the cluster are dropped entirely. Returns a
+    /// `ClustersForCustomKeyboard` error if the board isn't one of the known keyboard types.
+    pub fn cluster(&self, layer: &str, cluster: Cluster) -> Result<Layer> {
+        let layer = self.layer_or_err(layer)?;
+
+        let board = match &self.board {
+            ParseKeyboard::Named(board) if !matches!(board, KeyboardType::Custom(_)) => board,
+            _ => return Err(DE::ClustersForCustomKeyboard.into()),
+        };
+
+        let anchor = self.resolved_anchor()?;
+
+        let keys = layer
+            .rows()
+            .enumerate()
+            .map(|(row, keys)| {
+                keys.iter()
+                    .enumerate()
+                    .filter(|(col, _)| {
+                        board.cluster(row + anchor.y(), col + anchor.x()) == Some(cluster)
+                    })
+                    .map(|(_, k)| k.clone())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect::<Vec<_>>();
+
+        Ok(keys.into())
+    }
+
+    /// Get every position on a layer that is pressed with a specific finger, as `(row, col, key)`, in
+    /// row-major order. Like [`key_finger_pairs_lossy()`](crate::DofIntermediate::key_finger_pairs_lossy),
+    /// keys without a finger are skipped.
+    pub fn keys_for_finger(
+        &self,
+        layer: &str,
+        finger: Finger,
+    ) -> Result<Vec<(usize, usize, &Key)>> {
+        let fingering = self.unshaped_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let keys = layer
+            .rows()
+            .zip(fingering.rows())
+            .enumerate()
+            .flat_map(|(row, (key_row, finger_row))| {
+                key_row
+                    .iter()
+                    .zip(finger_row)
+                    .enumerate()
+                    .filter(|(_, (_, &f))| f == finger)
+                    .map(move |(col, (key, _))| (row, col, key))
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    /// Get every position on a layer that is pressed with a thumb, as `(row, col, key, thumb)`, in row-major
+    /// order, where `thumb` is either `LT` or `RT`. Useful for looking at thumb usage separately from the
+    /// other eight fingers.
+    pub fn thumb_keys(&self, layer: &str) -> Result<Vec<(usize, usize, &Key, Finger)>> {
+        let fingering = self.unshaped_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let keys = layer
+            .rows()
+            .zip(fingering.rows())
+            .enumerate()
+            .flat_map(|(row, (key_row, finger_row))| {
+                key_row
+                    .iter()
+                    .zip(finger_row)
+                    .enumerate()
+                    .filter(|(_, (_, f))| f.is_thumb())
+                    .map(move |(col, (key, &f))| (row, col, key, f))
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    /// Get every distinct [`SpecialKey`](crate::dofinitions::SpecialKey) used on any layer of the layout.
+    pub fn specials_used(&self) -> BTreeSet<SpecialKey> {
+        self.layers.values().flat_map(specials_on).collect()
+    }
+
+    /// Get every distinct [`SpecialKey`](crate::dofinitions::SpecialKey) used on a specific layer.
+    pub fn specials_used_on(&self, layer: &str) -> Result<BTreeSet<SpecialKey>> {
+        let layer = self.layer_or_err(layer)?;
+
+        Ok(specials_on(layer).collect())
+    }
+
+    /// Get every position on a layer holding a specific [`SpecialKey`](crate::dofinitions::SpecialKey), as
+    /// `(row, col)` in row-major order.
+    pub fn positions_of_special(
+        &self,
+        layer: &str,
+        key: SpecialKey,
+    ) -> Result<Vec<(usize, usize)>> {
+        let layer = self.layer_or_err(layer)?;
+
+        let positions = layer
+            .rows()
+            .enumerate()
+            .flat_map(|(row, keys)| {
+                keys.iter()
+                    .enumerate()
+                    .filter(|(_, k)| matches!(k, Key::Special(s) if s == &key))
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect();
+
+        Ok(positions)
+    }
+
+    /// Get every character on the `shift` layer that isn't on the `base` layer, meaning it can only be
+    /// typed by holding shift. Only character keys are taken into account, words are ignored.
+    pub fn shifted_only_chars(&self, base: &str, shift: &str) -> Result<BTreeSet<char>> {
+        let base = chars_on(self.layer_or_err(base)?).collect::<BTreeSet<_>>();
+        let shift = self.layer_or_err(shift)?;
+
+        Ok(chars_on(shift).filter(|c| !base.contains(c)).collect())
+    }
+
+    /// Iterate over every character key on every layer, yielding the name of the layer, the character and
+    /// its row and column. Only [`Key::Char`](crate::dofinitions::Key::Char) keys are included.
+    pub fn all_char_positions(&self) -> impl Iterator<Item = (&str, char, usize, usize)> {
+        self.layers.iter().flat_map(|(name, layer)| {
+            layer.rows().enumerate().flat_map(move |(y, row)| {
+                row.iter().enumerate().filter_map(move |(x, k)| match k {
+                    Key::Char(c) => Some((name.as_str(), *c, y, x)),
+                    _ => None,
+                })
+            })
+        })
+    }
+
+    /// Get every layer name paired with the amount of keys on it that aren't
+    /// [`Key::Transparent`](crate::dofinitions::Key::Transparent), sorted from most to least keys. Layers
+    /// with the same amount of keys are sorted by name.
+    pub fn layers_by_size(&self) -> Vec<(&str, usize)> {
+        let mut sizes = self
+            .layers
+            .iter()
+            .map(|(name, l)| {
+                (
+                    name.as_str(),
+                    l.keys().filter(|k| !k.is_transparent()).count(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        sizes.sort_by(|(n1, s1), (n2, s2)| s2.cmp(s1).then(n1.cmp(n2)));
+
+        sizes
+    }
+
+    /// Get every position on a layer holding a [`Key::Transparent`](crate::dofinitions::Key::Transparent)
+    /// key that doesn't refer to anything, because `base` has an empty or transparent key at that position,
+    /// or no key at all. Such keys output nothing, just like an empty key, so they can be simplified.
+    pub fn redundant_transparents(&self, layer: &str, base: &str) -> Result<Vec<(usize, usize)>> {
+        let layer = self.layer_or_err(layer)?;
+        let base = self.layer_or_err(base)?;
+
+        let redundant = layer
+            .rows()
+            .enumerate()
+            .flat_map(|(row, keys)| {
+                keys.iter()
+                    .enumerate()
+                    .filter(move |(col, k)| {
+                        k.is_transparent()
+                            && base
+                                .inner()
+                                .get(row)
+                                .and_then(|r| r.get(*col))
+                                .is_none_or(|b| b.is_empty() || b.is_transparent())
+                    })
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect();
+
+        Ok(redundant)
+    }
+
+    /// Check if the fingering of a layer is symmetric across hands, meaning that mirroring every row
+    /// gives the same fingering with the fingers of both hands swapped. This only looks at which finger
+    /// presses which position, so the characters on the layer don't matter.
+    pub fn is_symmetric(&self, layer: &str) -> Result<bool> {
+        let fingering = self.explicit_fingering_for(layer)?;
+
+        let symmetric = fingering.rows().all(|row| {
+            row.iter()
+                .zip(row.iter().rev())
+                .all(|(f, mirror)| &f.mirrored() == mirror)
+        });
+
+        Ok(symmetric)
+    }
+
+    /// Get every position on a layer that doesn't have a finger assigned to it, because its row in the
+    /// fingering is too short or missing entirely, or because it's assigned `Finger::Unassigned`.
+    pub fn unfingered_keys(&self, layer: &str) -> Result<Vec<Pos>> {
+        let fingering = self.unshaped_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let unfingered = layer
+            .rows()
+            .enumerate()
+            .flat_map(|(row, key_row)| {
+                let fingers = fingering.inner().get(row).map(Vec::as_slice).unwrap_or(&[]);
+
+                (0..key_row.len())
+                    .filter(|&col| fingers.get(col).is_none_or(Finger::is_unassigned))
+                    .map(move |col| Pos::new(row, col))
+            })
+            .collect();
+
+        Ok(unfingered)
+    }
+
+    /// Get the fraction of positions on a layer that have a finger assigned to them. A value below 1.0
+    /// means some keys are unfingered, which can be inspected with
+    /// [`unfingered_keys()`](crate::DofIntermediate::unfingered_keys).
+    pub fn fingering_coverage(&self, layer: &str) -> Result<f64> {
+        let unfingered = self.unfingered_keys(layer)?.len();
+        let total = self.layer_or_err(layer)?.keys().count();
+
+        match total {
+            0 => Ok(1.0),
+            total => Ok((total - unfingered) as f64 / total as f64),
+        }
+    }
+
+    /// Find the shortest sequence of layers to switch to, through [`Key::Layer`](crate::dofinitions::Key::Layer)
+    /// keys, to reach a layer containing a character when starting from the `start` layer. If the character
+    /// is on `start` the path is empty. Returns `None` if the character can't be reached or `start` doesn't
+    /// exist. If multiple paths are equally short, the one through the first layer keys in row-major
+    /// order is returned.
+    pub fn access_path(&self, c: char, start: &str) -> Option<Vec<String>> {
+        let has_char = |name: &str| {
+            self.layers
+                .get(name)
+                .is_some_and(|l| l.keys().any(|k| k.tap() == &Key::Char(c)))
+        };
+
+        self.layers.get(start)?;
+
+        let mut visited = BTreeSet::from([start]);
+        let mut queue = VecDeque::from([(start, Vec::new())]);
+
+        while let Some((name, path)) = queue.pop_front() {
+            if has_char(name) {
+                return Some(path);
+            }
+
+            let Some(layer) = self.layers.get(name) else {
+                continue;
+            };
+
+            for to in layer.keys().filter_map(Key::layer_target) {
+                if visited.insert(to) {
+                    let mut path = path.clone();
+                    path.push(to.to_owned());
+                    queue.push_back((to, path));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// List every layer key on the layout as `(from_layer, (row, col), to_layer)`, where `from_layer` is
+    /// the layer the key is on. Layers are visited in the order of the [`LayerMap`](crate::LayerMap), keys in
+    /// row-major order.
+    pub fn transitions(&self) -> Vec<(&str, (usize, usize), &str)> {
+        self.layers
+            .iter()
+            .flat_map(|(name, layer)| {
+                layer.rows().enumerate().flat_map(move |(row, keys)| {
+                    keys.iter().enumerate().filter_map(move |(col, k)| {
+                        k.layer_target().map(|to| (name.as_str(), (row, col), to))
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// Get a `'static` reference to a finger, for fingers of fingerings that aren't stored on the layout.
+const fn static_finger(finger: Finger) -> &'static Finger {
+    use Finger::*;
+
+    match finger {
+        LP => &LP,
+        LR => &LR,
+        LM => &LM,
+        LI => &LI,
+        LT => &LT,
+        RT => &RT,
+        RI => &RI,
+        RM => &RM,
+        RR => &RR,
+        RP => &RP,
+        Unassigned => &Unassigned,
+    }
+}
+
+fn specials_on(layer: &Layer) -> impl Iterator<Item = SpecialKey> + '_ {
+    layer.keys().filter_map(|k| match k {
+        Key::Special(s) => Some(s.clone()),
+        _ => None,
+    })
+}
+
+fn chars_on(layer: &Layer) -> impl Iterator<Item = char> + '_ {
+    layer.keys().filter_map(|k| match k.tap() {
+        Key::Char(c) => Some(*c),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +630,7 @@ mod tests {
         let buggy_json = serde_json::from_str::<Dof>(buggy).expect("couldn't parse json");
 
         assert_eq!(buggy_json.get(Key::Char('a')), [("main", (1, 5)).into()]);
+        #[cfg(not(feature = "preserve_order"))]
         assert_eq!(
             buggy_json.get(Key::Transparent),
             [
@@ -217,6 +640,16 @@ mod tests {
                 ("shift", (2, 2)).into()
             ]
         );
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            buggy_json.get(Key::Transparent),
+            [
+                ("l2", (2, 0)).into(),
+                ("shift", (2, 2)).into(),
+                ("l2s", (2, 0)).into(),
+                ("l2s", (2, 2)).into()
+            ]
+        );
     }
 
     #[test]
@@ -278,4 +711,420 @@ mod tests {
 
         assert_eq!(minimal_json, minimal_clone);
     }
+
+    #[test]
+    fn key_finger_pairs_lossy() {
+        use crate::{Fingering, ParsedFingering};
+        use Finger::*;
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let pairs = inter
+            .key_finger_pairs_lossy("main")
+            .expect("couldn't pair keys")
+            .collect::<Vec<_>>();
+
+        assert_eq!(pairs.len(), 31);
+        assert_eq!(pairs[0], (&Key::Char('q'), &LP));
+        assert_eq!(pairs[30], (&Key::Char('/'), &RP));
+
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![
+            vec![LP, LR, LM],
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP],
+        ])));
+
+        let pairs = inter
+            .key_finger_pairs_lossy("main")
+            .expect("couldn't pair keys")
+            .collect::<Vec<_>>();
+
+        assert_eq!(pairs.len(), 14);
+        assert_eq!(pairs[13], (&Key::Char('\''), &RP));
+
+        // Explicit fingerings are borrowed rather than copied.
+        let finger = match &inter.fingering {
+            Some(ParsedFingering::Explicit(f)) => &f.inner()[0][0],
+            f => panic!("expected an explicit fingering, found {f:?}"),
+        };
+        assert!(std::ptr::eq(pairs[0].1, finger));
+
+        assert!(inter.key_finger_pairs_lossy("altgr").is_err());
+    }
+
+    #[test]
+    fn dedup_layers() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let main = inter.layers["main"].clone();
+        let mut l2 = main.clone();
+        l2.0[0][0] = Key::Layer { name: "l3".into() };
+
+        inter.layers.insert("l1".into(), main.clone());
+        inter.layers.insert("l2".into(), l2.clone());
+        inter.layers.insert("l3".into(), l2);
+        inter.layers.get_mut("main").unwrap().0[0][0] = Key::Layer { name: "l1".into() };
+
+        let renames = inter.dedup_layers();
+
+        assert_eq!(renames, [("l3".to_string(), "l2".to_string())]);
+        #[cfg(not(feature = "preserve_order"))]
+        assert_eq!(
+            inter.layers.keys().collect::<Vec<_>>(),
+            ["l1", "l2", "main"]
+        );
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            inter.layers.keys().collect::<Vec<_>>(),
+            ["main", "l1", "l2"]
+        );
+        assert_eq!(inter.layers["l2"].0[0][0], Key::Layer { name: "l2".into() });
+
+        let renames = inter.dedup_layers();
+
+        assert!(renames.is_empty());
+    }
+
+    #[test]
+    fn finger_for_char() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.finger_for_char("main", 'q'), Ok(Some(Finger::LP)));
+        assert_eq!(inter.finger_for_char("main", 'b'), Ok(Some(Finger::LI)));
+        assert_eq!(inter.finger_for_char("main", 'z'), Ok(Some(Finger::LR)));
+        assert_eq!(inter.finger_for_char("main", 'ß'), Ok(None));
+        assert!(inter.finger_for_char("altgr", 'q').is_err());
+    }
+
+    #[test]
+    fn cluster() {
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.cluster("main", Cluster::Alphas),
+            Err(DE::ClustersForCustomKeyboard.into())
+        );
+
+        inter.board = ParseKeyboard::Named(KeyboardType::Ansi);
+
+        let numbers = inter
+            .cluster("main", Cluster::Numbers)
+            .expect("couldn't get numbers");
+        assert_eq!(numbers.rows().count(), 1);
+        assert_eq!(numbers.inner()[0].len(), 13);
+        assert_eq!(numbers.inner()[0][1], Key::Char('1'));
+
+        let alphas = inter
+            .cluster("main", Cluster::Alphas)
+            .expect("couldn't get alphas");
+        assert_eq!(alphas.shape(), vec![13, 11, 10].into());
+        assert_eq!(alphas.inner()[0][0], Key::Char('q'));
+        assert_eq!(alphas.inner()[0][12], Key::Char('\\'));
+
+        let modifiers = inter
+            .cluster("main", Cluster::Modifiers)
+            .expect("couldn't get modifiers");
+        assert_eq!(modifiers.shape(), vec![1, 1, 2, 2, 8].into());
+        assert_eq!(modifiers.inner()[2][1], Key::Special(SpecialKey::Enter));
+
+        assert_eq!(inter.cluster("main", Cluster::Nav), Ok(Layer::from(vec![])));
+
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        assert_eq!(
+            inter.cluster("main", Cluster::Alphas),
+            inter.main_layer().cloned()
+        );
+
+        let iso = include_str!("../example_dofs/iso.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(iso).expect("couldn't parse json");
+
+        let alphas = inter
+            .cluster("main", Cluster::Alphas)
+            .expect("couldn't get alphas");
+        assert_eq!(alphas.shape(), vec![12, 12, 11].into());
+        assert_eq!(alphas.inner()[1][11], Key::Char('#'));
+        assert_eq!(alphas.inner()[2][0], Key::Char('\\'));
+        assert_eq!(
+            inter.cluster("main", Cluster::Modifiers),
+            Ok(Layer::from(vec![vec![Key::Special(SpecialKey::IsoEnter)]]))
+        );
+    }
+
+    #[test]
+    fn keys_for_finger() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.keys_for_finger("main", Finger::LP),
+            Ok(vec![(0, 0, &Key::Char('q')), (1, 0, &Key::Char('a'))])
+        );
+        assert_eq!(
+            inter.keys_for_finger("main", Finger::RP),
+            Ok(vec![
+                (0, 9, &Key::Char('p')),
+                (1, 9, &Key::Char(';')),
+                (1, 10, &Key::Char('\'')),
+                (2, 9, &Key::Char('/')),
+            ])
+        );
+        assert_eq!(inter.keys_for_finger("main", Finger::LT), Ok(vec![]));
+        assert!(inter.keys_for_finger("altgr", Finger::LP).is_err());
+    }
+
+    #[test]
+    fn thumb_keys() {
+        use Finger::*;
+
+        let aptmak = include_str!("../example_dofs/aptmak.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(aptmak).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.thumb_keys("main"),
+            Ok(vec![
+                (3, 0, &Key::Empty, LT),
+                (3, 1, &Key::Special(SpecialKey::Space), LT),
+                (3, 2, &Key::Empty, LT),
+                (3, 3, &Key::Empty, RT),
+                (3, 4, &Key::Char('e'), RT),
+                (3, 5, &Key::Empty, RT),
+            ])
+        );
+
+        let minimal =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(minimal.thumb_keys("main"), Ok(vec![]));
+        assert!(minimal.thumb_keys("altgr").is_err());
+    }
+
+    #[test]
+    fn specials_used() {
+        use SpecialKey::*;
+
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.specials_used(),
+            BTreeSet::from_iter([Space, Tab, Enter, Shift, Caps, Backspace])
+        );
+        assert_eq!(
+            inter.specials_used_on("altgr"),
+            Ok(BTreeSet::from_iter([
+                Space, Tab, Enter, Shift, Caps, Backspace
+            ]))
+        );
+
+        let minimal =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert!(minimal.specials_used().is_empty());
+        assert!(minimal.specials_used_on("shift").is_err());
+    }
+
+    #[test]
+    fn all_char_positions() {
+        let inter = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse minimal dof");
+
+        let positions = inter.all_char_positions().collect::<Vec<_>>();
+
+        assert_eq!(positions.len(), 31);
+        assert_eq!(positions[0], ("main", 'q', 0, 0));
+        assert!(positions.contains(&("main", ';', 1, 9)));
+        assert!(positions.iter().all(|(name, ..)| *name == "main"));
+    }
+
+    #[test]
+    fn redundant_transparents() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(include_str!("../example_dofs/maximal.dof"))
+                .expect("couldn't parse maximal dof");
+
+        assert_eq!(inter.redundant_transparents("altgr", "main"), Ok(vec![]));
+
+        let altgr = inter.layers.get_mut("altgr").unwrap();
+        altgr.0[4][0] = Key::Transparent;
+        altgr.0[4].push(Key::Transparent);
+
+        assert_eq!(
+            inter.redundant_transparents("altgr", "main"),
+            Ok(vec![(4, 0), (4, 8)])
+        );
+        assert!(inter.redundant_transparents("altgr", "nope").is_err());
+    }
+
+    #[test]
+    fn positions_of_special() {
+        let inter =
+            serde_json::from_str::<DofIntermediate>(include_str!("../example_dofs/maximal.dof"))
+                .expect("couldn't parse maximal dof");
+
+        assert_eq!(
+            inter.positions_of_special("main", SpecialKey::Shift),
+            Ok(vec![(3, 0), (3, 11)])
+        );
+        assert_eq!(
+            inter.positions_of_special("altgr", SpecialKey::Enter),
+            Ok(vec![(2, 12)])
+        );
+        assert_eq!(
+            inter.positions_of_special("main", SpecialKey::Esc),
+            Ok(vec![])
+        );
+        assert!(inter
+            .positions_of_special("nope", SpecialKey::Shift)
+            .is_err());
+    }
+
+    #[test]
+    fn shifted_only_chars() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let mut shift = DofIntermediate::generate_shift_layer(inter.main_layer().unwrap());
+        shift.0[0][0] = Key::Char('q');
+        inter.layers.insert("shift".into(), shift);
+
+        let shifted = inter.shifted_only_chars("main", "shift").unwrap();
+
+        assert_eq!(shifted.len(), 30);
+        assert!(shifted.contains(&'W'));
+        assert!(shifted.contains(&'?'));
+        assert!(!shifted.contains(&'Q'));
+        assert!(!shifted.contains(&'q'));
+
+        assert!(inter.shifted_only_chars("main", "altgr").is_err());
+    }
+
+    #[test]
+    fn tap_hold_layers() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        let mut nav = inter.layers["main"].clone();
+        nav.0[0][0] = Key::Char('→');
+
+        inter.layers.insert("nav".into(), nav);
+        inter.layers.get_mut("main").unwrap().0[1][0] = Key::from("tap(a)hold(@nav)");
+
+        assert_eq!(inter.access_path('a', "main"), Some(vec![]));
+        assert_eq!(inter.access_path('→', "main"), Some(vec!["nav".into()]));
+        assert!(inter.transitions().contains(&("main", (1, 0), "nav")));
+        assert_eq!(inter.finger_for_char("main", 'a'), Ok(Some(Finger::LP)));
+    }
+
+    #[test]
+    fn layers_by_size() {
+        let buggy = include_str!("../example_dofs/buggy.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(buggy).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.layers_by_size(),
+            [("main", 18), ("l2", 17), ("shift", 17), ("l2s", 16)]
+        );
+    }
+
+    #[test]
+    fn fingering_coverage() {
+        use crate::{Fingering, ParsedFingering};
+        use Finger::*;
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.unfingered_keys("main"), Ok(vec![]));
+        assert_eq!(inter.fingering_coverage("main"), Ok(1.0));
+
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+        ])));
+
+        let unfingered = inter
+            .unfingered_keys("main")
+            .expect("couldn't get unfingered keys");
+
+        assert_eq!(unfingered.len(), 11);
+        assert_eq!(unfingered[0], Pos::new(1, 10));
+        assert_eq!(unfingered[1], Pos::new(2, 0));
+        assert_eq!(inter.fingering_coverage("main"), Ok(20.0 / 31.0));
+    }
+
+    #[test]
+    fn is_symmetric() {
+        use crate::{Fingering, ParsedFingering};
+        use Finger::*;
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        assert_eq!(inter.is_symmetric("main"), Ok(false));
+
+        inter.layers.insert(
+            "main".into(),
+            vec![vec![Key::Char('a'); 10], vec![Key::Char('b'); 10]].into(),
+        );
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+            vec![LP, LR, LM, LI, LT, RT, RI, RM, RR, RP],
+        ])));
+
+        assert_eq!(inter.is_symmetric("main"), Ok(true));
+        assert!(inter.is_symmetric("nonexistent").is_err());
+    }
+
+    #[test]
+    fn access_path() {
+        let buggy = include_str!("../example_dofs/buggy.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(buggy).expect("couldn't parse json");
+
+        let main = inter.layers["main"].clone();
+        let mut l3 = main.clone();
+        l3.0[0][0] = Key::Char('ß');
+        inter.layers.insert("l3".into(), l3);
+        inter.layers.get_mut("l2s").unwrap().0[0][0] = Key::Layer { name: "l3".into() };
+
+        assert_eq!(inter.access_path('a', "main"), Some(vec![]));
+        assert_eq!(
+            inter.access_path('ß', "main"),
+            Some(vec!["l2".to_string(), "l2s".to_string(), "l3".to_string()])
+        );
+        assert_eq!(inter.access_path('ß', "l2s"), Some(vec!["l3".to_string()]));
+        assert_eq!(inter.access_path('ß', "l3"), Some(vec![]));
+        assert_eq!(inter.access_path('€', "main"), None);
+        assert_eq!(inter.access_path('a', "altgr"), None);
+    }
+
+    #[test]
+    fn transitions() {
+        let buggy = include_str!("../example_dofs/buggy.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(buggy).expect("couldn't parse json");
+
+        #[cfg(not(feature = "preserve_order"))]
+        assert_eq!(
+            inter.transitions(),
+            vec![
+                ("l2", (2, 2), "l2s"),
+                ("main", (2, 0), "l2"),
+                ("shift", (2, 0), "l2s"),
+            ]
+        );
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            inter.transitions(),
+            vec![
+                ("main", (2, 0), "l2"),
+                ("l2", (2, 2), "l2s"),
+                ("shift", (2, 0), "l2s"),
+            ]
+        );
+
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        assert!(inter.transitions().is_empty());
+    }
 }
@@ -7,8 +7,8 @@ use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DisplayFromStr};
 
 use crate::{
-    keyboard_conv, Anchor, DofError, DofErrorInner as DE, Fingering, Keyboard, KeyboardType,
-    NamedFingering, Result,
+    interaction::Pos, keyboard_conv, Anchor, DofError, DofErrorInner as DE, Fingering, Keyboard,
+    KeyboardType, NamedFingering, Result,
 };
 
 /// Representation of a physical key on a keyboard, where `(x, y)` are the top left and the width and
@@ -164,7 +164,7 @@ pub struct RelativeKey {
 impl std::fmt::Display for RelativeKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.width {
-            w if w == 1.0 => write!(f, "k"),
+            1.0 => write!(f, "k"),
             w if w.fract() == 0.0 => write!(f, "{}k", w as u64),
             w => write!(f, "{w}k"),
         }
@@ -250,8 +250,9 @@ impl TryFrom<KeyboardType> for PhysicalKeyboard {
                     phys_row(&[(1.25, 3), (6.25, 1), (1.25, 4)], 0.0, 4.0),
                 ];
 
-                // ISO enter. This is an approximation because in reality it is not actually a rectangle.
-                iso[1].push(PhysicalKey::xywh(13.75, 2.0, 1.5, 2.0));
+                // ISO enter, which lives at the end of the top letter row and spans down into the home
+                // row. This is an approximation because in reality it is not actually a rectangle.
+                iso[1].push(PhysicalKey::xywh(13.5, 1.0, 1.5, 2.0));
 
                 iso
             }
@@ -461,13 +462,101 @@ impl TryFrom<ParseKeyboard> for PhysicalKeyboard {
     }
 }
 
+serde_with::serde_conv!(
+    PointStr,
+    (f64, f64),
+    |&(x, y): &(f64, f64)| format!("{x} {y}"),
+    |s: String| -> Result<(f64, f64)> {
+        let vals = s
+            .split_whitespace()
+            .map(|v| v.parse::<f64>())
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        match vals.as_slice() {
+            &[x, y] => Ok((x, y)),
+            sl => Err(DE::ValueAmountError(sl.len(), s.trim().into()).into()),
+        }
+    }
+);
+
+/// Explicit description of the geometry of a board, for boards whose stagger or split can't be captured
+/// by a [`KeyboardType`](crate::KeyboardType). Each key can be given an `"x y"` coordinate of its center,
+/// and each row can be given an x-offset which is added to every key in that row. If no key coordinates
+/// are provided, keys are placed on a grid with one unit between them, shifted by the row offsets.
+#[serde_as]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    row_offsets: Vec<f64>,
+    #[serde_as(as = "Vec<Vec<PointStr>>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    keys: Vec<Vec<(f64, f64)>>,
+}
+
+impl Geometry {
+    /// Create a new geometry from per-row x-offsets and per-key `(x, y)` coordinates.
+    pub fn new(row_offsets: Vec<f64>, keys: Vec<Vec<(f64, f64)>>) -> Self {
+        Self { row_offsets, keys }
+    }
+
+    /// Get the x-offset of each row.
+    pub fn row_offsets(&self) -> &[f64] {
+        &self.row_offsets
+    }
+
+    /// Get the `(x, y)` coordinates of each key, excluding row offsets.
+    pub fn keys(&self) -> &[Vec<(f64, f64)>] {
+        &self.keys
+    }
+
+    /// Get the `(x, y)` coordinate of the center of the key on a certain `Pos`, including its row
+    /// offset. Returns `None` if key coordinates are provided but not for this position.
+    pub fn position(&self, pos: impl Into<Pos>) -> Option<(f64, f64)> {
+        let pos = pos.into();
+        let offset = self.row_offsets.get(pos.row()).copied().unwrap_or_default();
+
+        let (x, y) = match self.keys.is_empty() {
+            true => (pos.col() as f64, pos.row() as f64),
+            false => *self.keys.get(pos.row())?.get(pos.col())?,
+        };
+
+        Some((x + offset, y))
+    }
+
+    /// Get the euclidean distance between the centers of the keys on two positions.
+    pub fn distance(&self, pos1: impl Into<Pos>, pos2: impl Into<Pos>) -> Option<f64> {
+        let (x1, y1) = self.position(pos1)?;
+        let (x2, y2) = self.position(pos2)?;
+
+        Some((x1 - x2).hypot(y1 - y2))
+    }
+}
+
+impl From<&PhysicalKeyboard> for Geometry {
+    fn from(board: &PhysicalKeyboard) -> Self {
+        let keys = board
+            .rows()
+            .map(|r| {
+                r.iter()
+                    .map(|k| (k.x + k.width / 2.0, k.y + k.height / 2.0))
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            row_offsets: vec![],
+            keys,
+        }
+    }
+}
+
 pub(crate) fn phys_row(widths: &[(f64, usize)], x_offset: f64, y_offset: f64) -> Vec<PhysicalKey> {
     let mut x = x_offset;
 
     widths
         .iter()
         .copied()
-        .flat_map(|(width, count)| std::iter::repeat(width).take(count))
+        .flat_map(|(width, count)| std::iter::repeat_n(width, count))
         .map(|w| {
             let pk = PhysicalKey::xyw(x, y_offset, w);
             x += w;
@@ -659,4 +748,58 @@ mod tests {
         assert_eq!(board.inner()[2].len(), 4);
         assert_eq!(board.inner()[0][3].x, 6.0);
     }
+
+    #[test]
+    fn geometry() {
+        let geometry_str = r#"{
+            "row_offsets": [0, 0.25],
+            "keys": [
+                ["0 0", "1 0", "2 0.5"],
+                ["0 1", "1 1"]
+            ]
+        }"#;
+
+        let geometry =
+            serde_json::from_str::<Geometry>(geometry_str).expect("parsing of Geometry failed");
+
+        assert_eq!(geometry.position((0, 2)), Some((2.0, 0.5)));
+        assert_eq!(geometry.position((1, 1)), Some((1.25, 1.0)));
+        assert_eq!(geometry.position((1, 2)), None);
+        assert_eq!(geometry.distance((0, 0), (0, 1)), Some(1.0));
+
+        let offsets_only = Geometry::new(vec![0.0, 0.5], vec![]);
+
+        assert_eq!(offsets_only.position((1, 3)), Some((3.5, 1.0)));
+
+        let reserialized = serde_json::to_string(&geometry).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<Geometry>(&reserialized).unwrap(),
+            geometry
+        );
+        assert!(serde_json::from_str::<Geometry>(r#"{"keys": [["0"]]}"#).is_err());
+    }
+
+    #[test]
+    fn geometry_from_physical_keyboard() {
+        let board = PhysicalKeyboard(vec![vec![
+            PhysicalKey::xy(0.0, 0.0),
+            PhysicalKey::xywh(1.0, 0.0, 2.0, 1.0),
+        ]]);
+
+        let geometry = Geometry::from(&board);
+
+        assert_eq!(geometry.position((0, 0)), Some((0.5, 0.5)));
+        assert_eq!(geometry.position((0, 1)), Some((2.0, 0.5)));
+    }
+
+    #[test]
+    fn iso_enter() {
+        let iso = PhysicalKeyboard::try_from(KeyboardType::Iso).expect("couldn't create iso board");
+
+        assert_eq!(iso.inner()[1].len(), 14);
+        assert_eq!(iso.inner()[2].len(), 13);
+        assert_eq!(iso.inner()[3].len(), 13);
+        assert_eq!(iso.inner()[1][13], PhysicalKey::xywh(13.5, 1.0, 1.5, 2.0));
+    }
 }
@@ -0,0 +1,273 @@
+//! Contains [`LayerMap`](crate::LayerMap), the map of layer names to layers of a layout, along with the
+//! iterators it returns.
+//!
+//! Layers are sorted by name, unless the `preserve_order` feature is enabled, in which case they keep the
+//! order they were written or inserted in. The API is the same either way, so enabling the feature only
+//! changes the order layers are visited in.
+
+use std::{borrow::Borrow, hash::Hash, ops::Index};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Layer;
+
+#[cfg(not(feature = "preserve_order"))]
+type Map = std::collections::BTreeMap<String, Layer>;
+
+#[cfg(feature = "preserve_order")]
+type Map = indexmap::IndexMap<String, Layer>;
+
+/// Map of layer names to layers. Layers are sorted by name, unless the `preserve_order` feature is enabled,
+/// in which case they keep the order they were written or inserted in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LayerMap(Map);
+
+impl LayerMap {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the amount of layers.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no layers.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get a layer by name.
+    pub fn get<Q>(&self, name: &Q) -> Option<&Layer>
+    where
+        String: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.0.get(name)
+    }
+
+    /// Get a mutable reference to a layer by name.
+    pub fn get_mut<Q>(&mut self, name: &Q) -> Option<&mut Layer>
+    where
+        String: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.0.get_mut(name)
+    }
+
+    /// Check if a layer with this name exists.
+    pub fn contains_key<Q>(&self, name: &Q) -> bool
+    where
+        String: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        self.0.contains_key(name)
+    }
+
+    /// Insert a layer, returning the layer that previously had this name, if any. A layer that replaces
+    /// another one keeps its position.
+    pub fn insert(&mut self, name: String, layer: Layer) -> Option<Layer> {
+        self.0.insert(name, layer)
+    }
+
+    /// Remove a layer by name, returning it if it existed. The other layers keep their order.
+    pub fn remove<Q>(&mut self, name: &Q) -> Option<Layer>
+    where
+        String: Borrow<Q>,
+        Q: Ord + Hash + ?Sized,
+    {
+        #[cfg(not(feature = "preserve_order"))]
+        return self.0.remove(name);
+
+        #[cfg(feature = "preserve_order")]
+        return self.0.shift_remove(name);
+    }
+
+    /// Only keep the layers for which `keep` returns `true`.
+    pub fn retain(&mut self, keep: impl FnMut(&String, &mut Layer) -> bool) {
+        self.0.retain(keep)
+    }
+
+    /// Iterate over the names of the layers.
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &String> + ExactSizeIterator {
+        self.0.keys()
+    }
+
+    /// Iterate over the layers.
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Layer> + ExactSizeIterator {
+        self.0.values()
+    }
+
+    /// Iterate over mutable references to the layers.
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut Layer> + ExactSizeIterator {
+        self.0.values_mut()
+    }
+
+    /// Iterate over the names of the layers along with the layers.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.0.iter())
+    }
+
+    /// Iterate over the names of the layers along with mutable references to the layers.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut(self.0.iter_mut())
+    }
+}
+
+impl<Q> Index<&Q> for LayerMap
+where
+    String: Borrow<Q>,
+    Q: Ord + Hash + ?Sized,
+{
+    type Output = Layer;
+
+    /// Get a layer by name. Panics if it doesn't exist.
+    fn index(&self, name: &Q) -> &Layer {
+        self.get(name).expect("layer doesn't exist")
+    }
+}
+
+impl FromIterator<(String, Layer)> for LayerMap {
+    fn from_iter<T: IntoIterator<Item = (String, Layer)>>(iter: T) -> Self {
+        Self(Map::from_iter(iter))
+    }
+}
+
+impl Extend<(String, Layer)> for LayerMap {
+    fn extend<T: IntoIterator<Item = (String, Layer)>>(&mut self, iter: T) {
+        self.0.extend(iter)
+    }
+}
+
+impl<const N: usize> From<[(String, Layer); N]> for LayerMap {
+    fn from(layers: [(String, Layer); N]) -> Self {
+        Self::from_iter(layers)
+    }
+}
+
+macro_rules! layer_map_iter {
+    ($(#[$doc:meta])* $name:ident$(<$lt:lifetime>)?, $item:ty, $btree:ty, $index:ty) => {
+        $(#[$doc])*
+        #[derive(Debug)]
+        pub struct $name$(<$lt>)?(
+            #[cfg(not(feature = "preserve_order"))] $btree,
+            #[cfg(feature = "preserve_order")] $index,
+        );
+
+        impl$(<$lt>)? Iterator for $name$(<$lt>)? {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+
+        impl$(<$lt>)? DoubleEndedIterator for $name$(<$lt>)? {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                self.0.next_back()
+            }
+        }
+
+        impl$(<$lt>)? ExactSizeIterator for $name$(<$lt>)? {}
+    };
+}
+
+layer_map_iter!(
+    /// Iterator over the names of the layers of a [`LayerMap`](crate::LayerMap) along with the layers.
+    Iter<'a>,
+    (&'a String, &'a Layer),
+    std::collections::btree_map::Iter<'a, String, Layer>,
+    indexmap::map::Iter<'a, String, Layer>
+);
+
+layer_map_iter!(
+    /// Iterator over the names of the layers of a [`LayerMap`](crate::LayerMap) along with mutable
+    /// references to the layers.
+    IterMut<'a>,
+    (&'a String, &'a mut Layer),
+    std::collections::btree_map::IterMut<'a, String, Layer>,
+    indexmap::map::IterMut<'a, String, Layer>
+);
+
+layer_map_iter!(
+    /// Owning iterator over the names of the layers of a [`LayerMap`](crate::LayerMap) along with the
+    /// layers.
+    IntoIter,
+    (String, Layer),
+    std::collections::btree_map::IntoIter<String, Layer>,
+    indexmap::map::IntoIter<String, Layer>
+);
+
+impl<'a> IntoIterator for &'a LayerMap {
+    type Item = (&'a String, &'a Layer);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut LayerMap {
+    type Item = (&'a String, &'a mut Layer);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl IntoIterator for LayerMap {
+    type Item = (String, Layer);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.0.into_iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order() {
+        let layer = Layer::from(vec![vec![crate::dofinitions::Key::Empty]]);
+        let mut layers = LayerMap::from([
+            ("main".to_string(), layer.clone()),
+            ("shift".to_string(), layer.clone()),
+            ("altgr".to_string(), layer.clone()),
+        ]);
+
+        #[cfg(not(feature = "preserve_order"))]
+        assert_eq!(
+            layers.keys().collect::<Vec<_>>(),
+            ["altgr", "main", "shift"]
+        );
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(
+            layers.keys().collect::<Vec<_>>(),
+            ["main", "shift", "altgr"]
+        );
+
+        assert!(layers.insert("main".into(), layer.clone()).is_some());
+        assert!(layers.remove("shift").is_some());
+        assert!(!layers.contains_key("shift"));
+
+        #[cfg(not(feature = "preserve_order"))]
+        assert_eq!(layers.keys().collect::<Vec<_>>(), ["altgr", "main"]);
+        #[cfg(feature = "preserve_order")]
+        assert_eq!(layers.keys().collect::<Vec<_>>(), ["main", "altgr"]);
+
+        let json = serde_json::to_string(&layers).unwrap();
+        assert_eq!(serde_json::from_str::<LayerMap>(&json).unwrap(), layers);
+        assert_eq!(layers.into_iter().count(), 2);
+    }
+}
@@ -1,22 +1,43 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
+pub mod analysis;
+#[cfg(feature = "binary")]
+pub mod binary;
+#[cfg(feature = "checksum")]
+pub mod checksum;
 pub mod dofinitions;
+#[cfg(feature = "gzip")]
+pub mod gzip;
 pub mod interaction;
 pub mod keyboard;
+pub mod layer_map;
 mod macros;
 pub mod prelude;
+pub mod render;
+#[cfg(feature = "schema")]
+pub mod schema;
+
+pub use layer_map::LayerMap;
+#[cfg(feature = "schema")]
+pub use schema::json_schema;
 
 use interaction::{KeyPos, Pos};
-use keyboard::{ParseKeyboard, PhysicalKey, PhysicalKeyboard};
+use keyboard::{Geometry, ParseKeyboard, PhysicalKey, PhysicalKeyboard};
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, skip_serializing_none, DisplayFromStr};
 use thiserror::Error;
+use unicode_width::UnicodeWidthStr;
 
 use std::collections::BTreeMap;
 
 use dofinitions::*;
 
+/// The most recent version of the .dof format this version of the library supports. Files can declare the
+/// version they target with the `version` field, and files targeting a newer version are refused. This is
+/// versioned separately from the library and only changes when the format does.
+pub const CURRENT_VERSION: &str = "0.19.0";
+
 /// A struct to represent the dof keyboard layout spec. This struct is useful for interacting with dofs
 /// and parsing to/from .dof using [`serde_json`](https://crates.io/crates/serde_json). For converting
 /// other formats into dofs, consider taking a look at [`DofIntermediate`](crate::DofIntermediate).
@@ -41,6 +62,7 @@ use dofinitions::*;
 #[serde(try_from = "DofIntermediate", into = "DofIntermediate")]
 pub struct Dof {
     name: String,
+    version: Option<String>,
     authors: Option<Vec<String>>,
     board: PhysicalKeyboard,
     parsed_board: ParseKeyboard,
@@ -48,12 +70,25 @@ pub struct Dof {
     description: Option<String>,
     languages: Vec<Language>,
     link: Option<String>,
-    layers: BTreeMap<String, Layer>,
+    tags: Vec<String>,
+    corpus: Option<String>,
+    layers: LayerMap,
+    base_layer: String,
     anchor: Anchor,
+    geometry: Option<Geometry>,
+    key_ids: Option<Vec<Vec<u16>>>,
+    thumbs: Option<Layer>,
+    thumb_fingering: Option<Fingering>,
+    auto_shift: bool,
+    case_insensitive_layers: bool,
     // alt_fingerings: Option<Vec<String>>,
     // combos: Option<HashMap<String, String>>,
     fingering: Fingering,
     fingering_name: Option<NamedFingering>,
+    fingerings: Option<BTreeMap<String, ParsedFingering>>,
+    layer_fingerings: BTreeMap<String, Fingering>,
+    locked: Option<Vec<Anchor>>,
+    checksum: Option<String>,
     has_generated_shift: bool,
 }
 
@@ -63,6 +98,11 @@ impl Dof {
         &self.name
     }
 
+    /// Get the optional version of the .dof format the layout targets.
+    pub fn format_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
     /// Get an optional slice of authors of the layout.
     pub fn authors(&self) -> Option<&[String]> {
         self.authors.as_deref()
@@ -97,22 +137,75 @@ impl Dof {
         self.link.as_deref()
     }
 
+    /// Get the tags of the layout, like `symmetric` or `35-key`. These are trimmed, lowercase and
+    /// deduplicated.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    /// Get the identifier of the corpus the layout was analyzed against, if provided.
+    pub fn corpus(&self) -> Option<&str> {
+        self.corpus.as_deref()
+    }
+
     /// Get a slice of [Language](crate::Language) this layout was intended to be used for.
     pub fn languages(&self) -> &[Language] {
         &self.languages
     }
 
     /// Get a map containing the layer names and its corresponding layer on the layout.
-    pub fn layers(&self) -> &BTreeMap<String, Layer> {
+    pub fn layers(&self) -> &LayerMap {
         &self.layers
     }
 
+    /// Get the name of the base layer, being the layer other layers are validated against and that
+    /// transparent keys refer to.
+    pub fn base_layer_name(&self) -> &str {
+        &self.base_layer
+    }
+
     /// Get the layout anchor, which specifies the coordinate of the top left corner of the layout compared to
     /// the physical keyboard it's on.
     pub const fn anchor(&self) -> Anchor {
         self.anchor
     }
 
+    /// Get the geometry of the layout. An explicitly provided geometry is preferred, otherwise it is
+    /// derived from the physical keyboard.
+    pub fn geometry(&self) -> Geometry {
+        match &self.geometry {
+            Some(g) => g.clone(),
+            None => Geometry::from(&self.board),
+        }
+    }
+
+    /// Get the firmware ids of the physical keys, if provided. These have the same shape as the layers.
+    pub fn key_ids(&self) -> Option<&[Vec<u16>]> {
+        self.key_ids.as_deref()
+    }
+
+    /// Get the thumb cluster of the layout, if provided. It is described separately from the layers and
+    /// can have any shape.
+    pub fn thumbs(&self) -> Option<&Layer> {
+        self.thumbs.as_ref()
+    }
+
+    /// Get the fingering of the thumb cluster, if provided. It has the same shape as the thumb cluster.
+    pub fn thumb_fingering(&self) -> Option<&Fingering> {
+        self.thumb_fingering.as_ref()
+    }
+
+    /// Check if the shift layer is generated from the base layer because the layout sets `auto_shift`.
+    pub const fn auto_shift(&self) -> bool {
+        self.auto_shift
+    }
+
+    /// Check if [`Key::Layer`](crate::dofinitions::Key::Layer) keys are matched to layer names
+    /// case-insensitively because the layout sets `case_insensitive_layers`.
+    pub const fn case_insensitive_layers(&self) -> bool {
+        self.case_insensitive_layers
+    }
+
     /// Get the shape of the fingering and layers of the dof
     pub fn shape(&self) -> Shape {
         self.fingering().shape()
@@ -124,16 +217,32 @@ impl Dof {
         &self.fingering
     }
 
+    /// Get the fingering of a specific layer, being its own fingering if the layout provides one through
+    /// `fingerings`, and the fingering of the layout otherwise.
+    pub fn fingering_for(&self, layer: &str) -> &Fingering {
+        self.layer_fingerings.get(layer).unwrap_or(&self.fingering)
+    }
+
     /// If present, get a specified type of fingering that the layout uses.
     pub fn fingering_name(&self) -> Option<&NamedFingering> {
         self.fingering_name.as_ref()
     }
 
-    /// Get the main layer of the layout. Contains a call to `expect()` but since creating a
-    /// `Dof` without a main layer is impossible, it should never fail.
+    /// Get the positions of keys that transformations of the layout leave in place, if provided.
+    pub fn locked(&self) -> Option<&[Anchor]> {
+        self.locked.as_deref()
+    }
+
+    /// Get the checksum of the content of the layout, if provided.
+    pub fn checksum(&self) -> Option<&str> {
+        self.checksum.as_deref()
+    }
+
+    /// Get the main layer of the layout, being the base layer. Contains a call to `expect()` but since
+    /// creating a `Dof` without a main layer is impossible, it should never fail.
     pub fn main_layer(&self) -> &Layer {
         self.layers
-            .get("main")
+            .get(&self.base_layer)
             .expect("Creating a Dof without a main layer should be impossible")
     }
 
@@ -152,7 +261,7 @@ impl Dof {
 
     /// Get a vector of keys with metadata for each key attached. This can be useful if you want
     /// to filter or any other way look at a specific set of keys on the keyboard.
-    pub fn keys(&self) -> Vec<DescriptiveKey> {
+    pub fn keys(&self) -> Vec<DescriptiveKey<'_>> {
         self.layers()
             .iter()
             .flat_map(|(name, layer)| {
@@ -180,32 +289,59 @@ impl TryFrom<DofIntermediate> for Dof {
     type Error = DofError;
 
     fn try_from(mut inter: DofIntermediate) -> std::result::Result<Self, Self::Error> {
+        inter.validate_version()?;
+
+        #[cfg(feature = "checksum")]
+        if inter.checksum.is_some() && !inter.verify_checksum()? {
+            return Err(DErr::ChecksumMismatch.into());
+        }
+
         let main_layer = inter.main_layer()?;
+        let base_layer = inter.base_layer_name().ok_or(DErr::NoMainLayer)?.to_owned();
+
+        let case_insensitive_layers = inter.case_insensitive_layers.unwrap_or_default();
 
-        inter.validate_layer_keys(main_layer)?;
+        match case_insensitive_layers {
+            true => inter.validate_layer_keys_ci(main_layer)?,
+            false => inter.validate_layer_keys(main_layer)?,
+        }
         inter.validate_layer_shapes(main_layer)?;
+        inter.validate_rectangular(main_layer)?;
+        inter.validate_key_ids(main_layer)?;
+        inter.validate_locked(main_layer)?;
+        inter.validate_thumbs()?;
 
         let explicit_fingering = inter.explicit_fingering(main_layer)?;
+        let layer_fingerings = inter
+            .fingerings
+            .iter()
+            .flatten()
+            .map(|(name, _)| Ok((name.clone(), inter.explicit_fingering_for(name)?)))
+            .collect::<Result<BTreeMap<_, _>>>()?;
         let implicit_fingering = match inter.fingering.clone().unwrap_or_default() {
             ParsedFingering::Implicit(f) => Some(f),
             _ => None,
         };
 
-        let has_generated_shift = if !inter.layers.contains_key("shift") {
-            inter.layers.insert(
-                "shift".into(),
-                DofIntermediate::generate_shift_layer(main_layer),
-            );
-            true
-        } else {
-            false
-        };
+        let auto_shift = inter.auto_shift.unwrap_or_default();
 
-        let anchor = match inter.anchor {
-            None => inter.board.anchor(),
-            Some(a) => a,
+        let has_generated_shift = match (auto_shift, inter.layers.contains_key("shift")) {
+            (true, true) => return Err(DErr::AutoShiftWithShiftLayer.into()),
+            (true, false) => {
+                let shift = DofIntermediate::generate_auto_shift_layer(main_layer);
+                inter.layers.insert("shift".into(), shift);
+                true
+            }
+            (false, false) => {
+                let shift = DofIntermediate::generate_shift_layer(main_layer);
+                inter.layers.insert("shift".into(), shift);
+                true
+            }
+            (false, true) => false,
         };
 
+        let anchor = inter.resolved_anchor()?;
+
         let board = PhysicalKeyboard::try_from(inter.board.clone())?
             .resized(anchor, explicit_fingering.shape())?
             .into_iter()
@@ -217,6 +353,8 @@ impl TryFrom<DofIntermediate> for Dof {
             .collect::<Vec<_>>()
             .into();
 
+        let tags = inter.normalized_tags();
+
         let languages = match inter.languages {
             Some(l) => l,
             None => vec![Language::default()],
@@ -224,6 +362,7 @@ impl TryFrom<DofIntermediate> for Dof {
 
         Ok(Self {
             name: inter.name,
+            version: inter.version,
             authors: inter.authors,
             board,
             parsed_board: inter.board,
@@ -231,10 +370,23 @@ impl TryFrom<DofIntermediate> for Dof {
             description: inter.description,
             languages,
             link: inter.link,
+            tags,
+            corpus: inter.corpus,
             layers: inter.layers,
+            base_layer,
             anchor,
+            geometry: inter.geometry,
+            key_ids: inter.key_ids,
+            thumbs: inter.thumbs,
+            thumb_fingering: inter.thumb_fingering,
+            auto_shift,
+            case_insensitive_layers,
             fingering: explicit_fingering,
             fingering_name: implicit_fingering,
+            fingerings: inter.fingerings,
+            layer_fingerings,
+            locked: inter.locked,
+            checksum: inter.checksum,
             has_generated_shift,
         })
     }
@@ -265,23 +417,42 @@ impl From<Dof> for DofIntermediate {
         let anchor = match &dof.parsed_board {
             ParseKeyboard::Named(n) => match n.anchor() {
                 a if a == dof.anchor => None,
-                a => Some(a),
+                _ => Some(ParsedAnchor::Position(dof.anchor)),
             },
             _ => None,
         };
 
-        DofIntermediate {
+        let mut inter = DofIntermediate {
             name: dof.name,
+            version: dof.version,
             authors: dof.authors,
             board: dof.parsed_board,
             year: dof.year,
             description: dof.description,
             languages,
             link: dof.link,
+            tags: (!dof.tags.is_empty()).then_some(dof.tags),
+            corpus: dof.corpus,
+            base_layer: (dof.base_layer != "main").then_some(dof.base_layer),
             layers: dof.layers,
             anchor,
+            geometry: dof.geometry,
+            key_ids: dof.key_ids,
+            thumbs: dof.thumbs,
+            thumb_fingering: dof.thumb_fingering,
+            auto_shift: dof.auto_shift.then_some(true),
+            case_insensitive_layers: dof.case_insensitive_layers.then_some(true),
+            fingerings: dof.fingerings,
             fingering,
-        }
+            locked: dof.locked,
+            checksum: dof.checksum,
+        };
+
+        // The checksum was computed over the layout as it was written, which can differ from this
+        // normalized form, so it's computed again.
+        inter.refresh_checksum();
+
+        inter
     }
 }
 
@@ -289,23 +460,73 @@ impl From<Dof> for DofIntermediate {
 enum DofErrorInner {
     #[error("This layout is missing a main layer")]
     NoMainLayer,
+    #[error("This layout targets version {0} of the format, which is newer than the supported version {1}")]
+    NewerVersion(String, &'static str),
+    #[error("'{0}' is not a valid format version, expected something like 1.2.3")]
+    InvalidVersion(String),
     #[error("Found these layer keys '{0:?}' however these layers do not actually exist")]
     LayersNotFound(Vec<String>),
     #[error("The shape of these layers: '{0:?}' are not the same as the main layer")]
     IncompatibleLayerShapes(Vec<String>),
+    #[error("Ortho layouts must have rows of equal length, found rows of length {0:?}")]
+    RaggedOrthoLayer(Vec<usize>),
+    #[error("The shape of the key ids does not match the shape of the main layer")]
+    IncompatibleKeyIdsShape,
+    #[error("The shape of the thumb fingering does not match the shape of the thumb cluster")]
+    IncompatibleThumbFingeringShape,
+    #[error("The layout sets auto_shift, so its shift layer is generated and can't be provided")]
+    AutoShiftWithShiftLayer,
     #[error("The layer shapes do not match the fingering shape")]
     IncompatibleFingeringShape,
+    #[error("The shape of the effort grid does not match the shape of the layer")]
+    IncompatibleEffortShape,
+    #[error("This layout has no fingering defined")]
+    NoFingering,
+    #[error(
+        "The layer and fingering don't have one finger per key, diverging at position ({0}, {1})"
+    )]
+    FingerCountMismatch(usize, usize),
     #[error("The provided layout + anchor don't fit within the given fingering")]
     LayoutDoesntFit,
     #[error("The anchor provided is bigger than the layout it is used for")]
     AnchorBiggerThanLayout,
-
+    #[error("The anchor character '{0}' does not exist on the main layer")]
+    AnchorCharNotFound(char),
+    #[error("The anchor at {0:?} points at a key that has no finger assigned")]
+    AnchorNotFingered(Anchor),
+    #[error("The anchor at {0:?} points at a key typed by {1}, expected an index finger")]
+    AnchorNotOnIndex(Anchor, Finger),
+    #[error("These locked positions are not on the main layer: {0:?}")]
+    LockedPositionsNotOnLayout(Vec<Anchor>),
+    #[error("These positions hold a modifier on the base layer but a character on the shift layer: {0:?}")]
+    ShiftedModifiers(Vec<(usize, usize)>),
+    #[error("These keys override a modifier or layer key of the base layer, as (layer, row, col): {0:?}")]
+    SkeletonOverridden(Vec<(String, usize, usize)>),
+    #[error("This layout has a layer with an empty name")]
+    EmptyLayerName,
+    #[error("These layer names only differ in capitalization: {0:?}")]
+    LayerNamesDifferInCase(Vec<String>),
+    #[error("These character keys are control characters or whitespace, as (layer, row, col, codepoint): {0:?}")]
+    UnprintableChars(Vec<(String, usize, usize, u32)>),
+
+    #[error("Can't convert between ANSI and ISO, the layout should be on a '{0}' board with its anchor at most at x = 1")]
+    UnsupportedIsoConversion(KeyboardType),
+    #[error("Can't remap the layout, it should be on a '{0}' board")]
+    UnexpectedBoard(KeyboardType),
+    #[error("Can't remap the layout, the mapping should assign a unique position to each of the {0} keys of every layer")]
+    InvalidRemapping(usize),
+    #[error("The fingering mixes fingers written as letters and as numbers")]
+    MixedFingerNotation,
     #[error("Couldn't parse Finger from '{0}'")]
     FingerParseError(String),
     #[error("Can't combine keyboard type '{0}' with fingering '{1}'")]
     UnsupportedKeyboardFingeringCombo(KeyboardType, NamedFingering),
+    #[error("Unknown fingering '{0}', did you mean '{1}'?")]
+    UnknownFingering(String, &'static str),
     #[error("Default fingering only exists for known keyboards: ansi, iso, ortho and colstag")]
     FingeringForCustomKeyboard,
+    #[error("Clusters only exist for known keyboards: ansi, iso, ortho and colstag")]
+    ClustersForCustomKeyboard,
 
     #[error("Couldn't parse physical key from '{0}' because a float couldn't be parsed")]
     KeyParseError(String),
@@ -319,7 +540,7 @@ enum DofErrorInner {
     #[error("the provided layer name '{0}' is invalid")]
     LayerDoesntExist(String),
     #[error("the given position ({0}, {1}) is not available on the keyboard")]
-    InvalidPosition(u8, u8),
+    InvalidPosition(usize, usize),
 
     #[error("{0}")]
     Infallible(#[from] std::convert::Infallible),
@@ -327,6 +548,20 @@ enum DofErrorInner {
     ParseFloatError(#[from] std::num::ParseFloatError),
     #[error("{0}")]
     ParseIntError(#[from] std::num::ParseIntError),
+    #[error("{0}")]
+    Json(String),
+    #[cfg(feature = "binary")]
+    #[error("{0}")]
+    Binary(String),
+    #[cfg(feature = "gzip")]
+    #[error("{0}")]
+    Gzip(String),
+    #[cfg(feature = "checksum")]
+    #[error("This layout has no checksum to verify")]
+    NoChecksum,
+    #[cfg(feature = "checksum")]
+    #[error("The checksum of this layout does not match its content")]
+    ChecksumMismatch,
 
     #[error("{0}")]
     Custom(String),
@@ -355,12 +590,41 @@ impl From<DofErrorInner> for DofError {
     }
 }
 
+impl From<serde_json::Error> for DofError {
+    fn from(value: serde_json::Error) -> Self {
+        DErr::Json(value.to_string()).into()
+    }
+}
+
+impl From<DofError> for serde_json::Error {
+    fn from(value: DofError) -> Self {
+        serde::de::Error::custom(value)
+    }
+}
+
+impl TryFrom<&DofIntermediate> for serde_json::Value {
+    type Error = DofError;
+
+    fn try_from(inter: &DofIntermediate) -> std::result::Result<Self, Self::Error> {
+        serde_json::to_value(inter).map_err(Into::into)
+    }
+}
+
+impl TryFrom<serde_json::Value> for DofIntermediate {
+    type Error = DofError;
+
+    fn try_from(value: serde_json::Value) -> std::result::Result<Self, Self::Error> {
+        serde_json::from_value(value).map_err(Into::into)
+    }
+}
+
 /// Used to represent the language(s) a layout is optimized for, containing the name of a language as well as
 /// a weight, the latter being useful for layouts that are made for a combination of languages with some
 /// amount of % split.
 ///
 /// The Default implementation of Language is English with weight 100.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Language {
     language: String,
     weight: usize,
@@ -453,14 +717,27 @@ pub trait Keyboard {
                     .map(|v| v.to_vec())
             })
             .collect::<Result<Vec<_>>>()
-            .map(Into::into)
     }
 }
 
 /// Struct that represents the fingering of each layout. It is an abstraction over `Vec<Vec<Finger>>`.
-#[serde_as]
+/// It also holds the notation each row is written in, which is available through
+/// [`notation()`](crate::Fingering::notation). Rows are serialized in the notation they were parsed in,
+/// so fingerings that are written differently, like `0` and `LP`, don't compare equal. A row that mixes
+/// both notations is serialized with letters.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Fingering(#[serde_as(as = "Vec<FingeringStrAsRow>")] Vec<Vec<Finger>>);
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct Fingering(Vec<Vec<Finger>>, Vec<RowNotation>);
+
+/// Notation the fingers of a row of a [`Fingering`](crate::Fingering) are written in. Rows that only
+/// contain `Finger::Unassigned`, or weren't parsed at all, use letters as that's how they are serialized.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum RowNotation {
+    #[default]
+    Letters,
+    Numeric,
+    Mixed,
+}
 
 impl Keyboard for Fingering {
     type K = Finger;
@@ -476,14 +753,201 @@ impl Keyboard for Fingering {
 
 impl From<Vec<Vec<Finger>>> for Fingering {
     fn from(f: Vec<Vec<Finger>>) -> Self {
-        Self(f)
+        let notations = vec![RowNotation::default(); f.len()];
+        Self(f, notations)
+    }
+}
+
+impl TryFrom<Vec<String>> for Fingering {
+    type Error = DofError;
+
+    fn try_from(rows: Vec<String>) -> std::result::Result<Self, Self::Error> {
+        let mut fingers = Vec::with_capacity(rows.len());
+        let mut notations = Vec::with_capacity(rows.len());
+
+        for row in rows {
+            let mut finger_row = Vec::new();
+            let (mut letters, mut numeric) = (false, false);
+
+            for token in row.split_whitespace() {
+                let finger = token.parse::<Finger>()?;
+
+                match finger {
+                    Finger::Unassigned => {}
+                    _ if token.chars().all(|c| c.is_ascii_digit()) => numeric = true,
+                    _ => letters = true,
+                }
+
+                finger_row.push(finger);
+            }
+
+            fingers.push(finger_row);
+            notations.push(match (letters, numeric) {
+                (true, true) => RowNotation::Mixed,
+                (false, true) => RowNotation::Numeric,
+                _ => RowNotation::Letters,
+            });
+        }
+
+        Ok(Self(fingers, notations))
+    }
+}
+
+impl From<Fingering> for Vec<String> {
+    fn from(fingering: Fingering) -> Self {
+        fingering
+            .0
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let numeric = fingering.1.get(i) == Some(&RowNotation::Numeric);
+
+                row.iter()
+                    .map(|&f| match f {
+                        Finger::Unassigned => f.to_string(),
+                        f if numeric => (f as u8).to_string(),
+                        f => f.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
     }
 }
 
-keyboard_conv!(Fingering, Finger, FingeringStrAsRow);
+impl Fingering {
+    /// Get the rows of the fingering as a slice into its storage, for passing them on without collecting
+    /// [`rows()`](crate::Keyboard::rows) first.
+    pub fn as_rows(&self) -> &[Vec<Finger>] {
+        &self.0
+    }
+
+    /// Find the known fingering this fingering corresponds to on a board, like `angle` or `traditional`.
+    /// The fingering is compared to each known fingering, cut to its shape at the default anchor of the
+    /// board. Returns `None` if it doesn't match any of them exactly.
+    pub fn identify(&self, board: KeyboardType) -> Option<NamedFingering> {
+        self.identify_at(&board, board.anchor())
+    }
+
+    /// Like [`identify()`](crate::Fingering::identify), but with the known fingerings cut off at `anchor`.
+    pub(crate) fn identify_at(
+        &self,
+        board: &KeyboardType,
+        anchor: Anchor,
+    ) -> Option<NamedFingering> {
+        use NamedFingering::*;
+
+        [Traditional, Angle, Wide, AngleWide]
+            .into_iter()
+            .find(|named| {
+                board
+                    .fingering(named)
+                    .and_then(|f| f.resized(anchor, self.shape()))
+                    .is_ok_and(|f| f == self.0)
+            })
+    }
+
+    /// Get the notation the fingers were written in when parsing, either letters like `LP` or numbers like
+    /// `0`. Returns a `MixedFingerNotation` error if both were used. Fingerings that weren't parsed, or
+    /// only contain `Finger::Unassigned`, use letters as that's how they are serialized.
+    pub fn notation(&self) -> Result<FingerNotation> {
+        notation_of([self])
+    }
+
+    /// Create a fingering with the notation of every row given, as stored by the binary format.
+    #[cfg(feature = "binary")]
+    pub(crate) fn with_row_notations(f: Vec<Vec<Finger>>, notations: Vec<RowNotation>) -> Self {
+        Self(f, notations)
+    }
+
+    /// Get the notation of every row of the fingering.
+    #[cfg(feature = "binary")]
+    pub(crate) fn row_notations(&self) -> &[RowNotation] {
+        &self.1
+    }
+
+    /// Convert the fingering to rows of numeric finger indices, where `LP` = 0, `LR` = 1 up to `RP` = 9.
+    /// This matches the numeric form fingers can be written in inside a .dof file. `Finger::Unassigned` is
+    /// converted to 10.
+    pub fn to_indices(&self) -> Vec<Vec<u8>> {
+        self.rows()
+            .map(|row| row.iter().map(|&f| f as u8).collect())
+            .collect()
+    }
+
+    /// Create a fingering from rows of numeric finger indices, where `LP` = 0, `LR` = 1 up to `RP` = 9,
+    /// and 10 is `Finger::Unassigned`. Returns a `FingerParseError` if any index is bigger than 10.
+    pub fn from_indices(indices: Vec<Vec<u8>>) -> Result<Self> {
+        indices
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|i| match i {
+                        10 => Ok(Finger::Unassigned),
+                        i => Finger::FINGERS
+                            .get(i as usize)
+                            .copied()
+                            .ok_or(DErr::FingerParseError(i.to_string()).into()),
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Into::into)
+    }
+
+    /// Extend the fingering to the given shape, appending `fill` to rows that are shorter than the
+    /// shape's row and adding rows if the shape has more of them. Rows that are already long enough are
+    /// left as is. Useful with `Finger::Unassigned` when only part of a layout has been fingered.
+    pub fn pad_to_shape(&mut self, shape: &[usize], fill: Finger) {
+        if self.0.len() < shape.len() {
+            self.0.resize(shape.len(), Vec::new());
+            self.1.resize(shape.len(), RowNotation::default());
+        }
+
+        for (row, &len) in self.0.iter_mut().zip(shape) {
+            if row.len() < len {
+                row.resize(len, fill);
+            }
+        }
+    }
+
+    /// Get every position where this fingering and `other` assign a different finger, along with the
+    /// finger of both, in row-major order. Returns an `IncompatibleFingeringShape` error if the shapes
+    /// of the fingerings don't match.
+    #[allow(clippy::type_complexity)]
+    pub fn diff(&self, other: &Fingering) -> Result<Vec<((usize, usize), Finger, Finger)>> {
+        if self.shape() != other.shape() {
+            return Err(DErr::IncompatibleFingeringShape.into());
+        }
+
+        let diff = self
+            .rows()
+            .zip(other.rows())
+            .enumerate()
+            .flat_map(|(y, (a, b))| {
+                a.iter()
+                    .zip(b)
+                    .enumerate()
+                    .filter(|(_, (fa, fb))| fa != fb)
+                    .map(move |(x, (&fa, &fb))| ((y, x), fa, fb))
+            })
+            .collect();
+
+        Ok(diff)
+    }
+
+    /// Split the fingering into the fingers of the left and the right hand, in that order. Every row is
+    /// kept, so a row of either hand is the part of the same row of the fingering that hand types, and
+    /// can be empty. Thumbs go to their own hand, unless `thumbs` moves all of them to one side, like the
+    /// side that presses space. Positions without a finger are left out.
+    pub fn by_hand(&self, thumbs: Option<Hand>) -> (Vec<Vec<Finger>>, Vec<Vec<Finger>>) {
+        split_by_hand(&self.0, &self.0, thumbs)
+    }
+}
 
 /// Abstraction over the way an actual .dof file is allowed to represent the fingering of a layout, being either
-/// explicit through providing a list of fingerings for each key, or implicit, by providing a name.
+/// explicit through providing a list of fingerings for each key, implicit, by providing a name, or not at all
+/// by providing `null` or an empty string.
 #[serde_as]
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -493,6 +957,28 @@ pub enum ParsedFingering {
     /// Covers the case where fingering is specified implicitly, by providing a name like `traditional`,
     /// `standard` or `angle`
     Implicit(#[serde_as(as = "DisplayFromStr")] NamedFingering),
+    /// Covers the case where the fingering is explicitly left unspecified, for example for layouts that are
+    /// still in progress. Anything that needs a fingering returns a `NoFingering` error.
+    None,
+}
+
+/// Deserialize the fingering of a [`DofIntermediate`](crate::DofIntermediate), where a missing fingering
+/// stays `None` so the default can be used, while `null` or an empty string becomes
+/// [`ParsedFingering::None`](crate::ParsedFingering::None).
+fn deserialize_fingering<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<ParsedFingering>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let fingering = match ParsedFingering::deserialize(deserializer)? {
+        ParsedFingering::Implicit(NamedFingering::Custom(name)) if name.is_empty() => {
+            ParsedFingering::None
+        }
+        f => f,
+    };
+
+    Ok(Some(fingering))
 }
 
 impl Default for ParsedFingering {
@@ -501,10 +987,106 @@ impl Default for ParsedFingering {
     }
 }
 
-/// An abstraction of `Vec<Vec<Key>>` to represent a layer on a layout.
-#[serde_as]
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
-pub struct Layer(#[serde_as(as = "Vec<LayerStrAsRow>")] Vec<Vec<Key>>);
+/// An abstraction of `Vec<Vec<Key>>` to represent a layer on a layout, with optional
+/// [`LayerMetadata`](crate::LayerMetadata) attached. In a .dof file, a layer is either an array of rows, or
+/// an object with the rows in a `keys` field and the metadata next to it. The latter is only used when
+/// serializing if any metadata is present.
+///
+/// Two layers are only equal if both their keys and their metadata are, so that layouts compare equal
+/// exactly when they serialize the same. To only compare the keys, compare
+/// [`inner()`](crate::Layer::inner) instead.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "LayerRepr", into = "LayerRepr")]
+pub struct Layer(Vec<Vec<Key>>, LayerMetadata);
+
+impl Layer {
+    /// Create an empty layer without any rows or metadata. Rows can be added with
+    /// [`push_row()`](crate::Layer::push_row) or [`push_row_str()`](crate::Layer::push_row_str).
+    pub fn new() -> Self {
+        Self(Vec::new(), LayerMetadata::default())
+    }
+
+    /// Append a row of keys to the layer.
+    pub fn push_row(&mut self, row: Vec<Key>) {
+        self.0.push(row);
+    }
+
+    /// Parse a row the way it's written in a .dof file, like `"q w e r t"`, and append it to the layer.
+    /// Every token currently parses to some key, so this doesn't fail yet, but it returns a `Result` so
+    /// rows can be validated while parsing without breaking the API.
+    pub fn push_row_str(&mut self, row: &str) -> Result<()> {
+        self.0
+            .push(split_row(row).into_iter().map(Key::from).collect());
+
+        Ok(())
+    }
+
+    /// Split the keys of the layer into the keys typed by the left and the right hand using `fingering`,
+    /// in the same way as [`Fingering::by_hand()`](crate::Fingering::by_hand). Keys that don't have a
+    /// finger in the fingering are left out.
+    pub fn by_hand(
+        &self,
+        fingering: &Fingering,
+        thumbs: Option<Hand>,
+    ) -> (Vec<Vec<Key>>, Vec<Vec<Key>>) {
+        split_by_hand(&self.0, &fingering.0, thumbs)
+    }
+
+    /// Get the metadata of the layer.
+    pub fn metadata(&self) -> &LayerMetadata {
+        &self.1
+    }
+
+    /// Get a mutable reference to the metadata of the layer.
+    pub fn metadata_mut(&mut self) -> &mut LayerMetadata {
+        &mut self.1
+    }
+
+    /// Attach metadata to the layer.
+    pub fn with_metadata(self, metadata: LayerMetadata) -> Self {
+        Self(self.0, metadata)
+    }
+
+    /// Get the rows of the layer as a slice into its storage, for passing them on without collecting
+    /// [`rows()`](crate::Keyboard::rows) first.
+    pub fn as_rows(&self) -> &[Vec<Key>] {
+        &self.0
+    }
+
+    /// Get the display width of the legend of each key, being the amount of terminal cells it takes up
+    /// when printed. Full-width glyphs like CJK characters take up two cells, while empty keys take up
+    /// one. Unlike [`shape()`](crate::Keyboard::shape), which counts keys, this is meant for aligning
+    /// keys when rendering a layer.
+    pub fn display_shape(&self) -> Vec<Vec<usize>> {
+        self.rows()
+            .map(|row| row.iter().map(|k| k.to_string().width()).collect())
+            .collect()
+    }
+
+    /// Count the amount of keys in each column, where the column of a key is its index within its row.
+    /// Because rows can have different lengths, the last columns may count fewer keys.
+    pub fn column_counts(&self) -> BTreeMap<usize, usize> {
+        self.column_counts_by(|_| true)
+    }
+
+    /// Like [`column_counts()`](crate::Layer::column_counts), but only counts keys that aren't
+    /// [`Key::Transparent`](crate::dofinitions::Key::Transparent).
+    pub fn non_transparent_column_counts(&self) -> BTreeMap<usize, usize> {
+        self.column_counts_by(|k| !k.is_transparent())
+    }
+
+    fn column_counts_by(&self, include: impl Fn(&Key) -> bool) -> BTreeMap<usize, usize> {
+        let mut counts = BTreeMap::new();
+
+        for row in self.rows() {
+            for (x, _) in row.iter().enumerate().filter(|(_, k)| include(k)) {
+                *counts.entry(x).or_insert(0) += 1;
+            }
+        }
+
+        counts
+    }
+}
 
 impl Keyboard for Layer {
     type K = Key;
@@ -520,11 +1102,180 @@ impl Keyboard for Layer {
 
 impl From<Vec<Vec<Key>>> for Layer {
     fn from(f: Vec<Vec<Key>>) -> Self {
-        Self(f)
+        Self(f, LayerMetadata::default())
+    }
+}
+
+serde_with::serde_conv!(
+    LayerStrAsRow,
+    Vec<Key>,
+    |row: &Vec<Key>| {
+        row.iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    },
+    |line: String| -> std::result::Result<_, std::convert::Infallible> {
+        Ok(split_row(&line).into_iter().map(Key::from).collect())
+    }
+);
+
+/// Split a row of a layer into its keys. Keys are separated by whitespace, except for keys wrapped in
+/// double quotes like `"Caps Lock"`, which are kept whole, quotes included. A key only counts as quoted
+/// if the opening quote is followed by, and the closing quote preceded by, something other than
+/// whitespace, and the closing quote ends the key. This way a lone `"` is still a key of its own.
+/// Tap-hold keys with quoted parts, like `tap("Caps Lock")hold(ctl)`, are kept whole as well.
+fn split_row(line: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut rest = line.trim_start();
+
+    while !rest.is_empty() {
+        let end = quoted_len(rest)
+            .or_else(|| tap_hold_len(rest))
+            .unwrap_or_else(|| rest.find(char::is_whitespace).unwrap_or(rest.len()));
+
+        keys.push(&rest[..end]);
+        rest = rest[end..].trim_start();
+    }
+
+    keys
+}
+
+/// Get the notation shared by the rows of all `fingerings`, ignoring rows without any assigned finger.
+/// Returns a `MixedFingerNotation` error if both letters and numbers are used.
+fn notation_of<'a>(fingerings: impl IntoIterator<Item = &'a Fingering>) -> Result<FingerNotation> {
+    let (mut letters, mut numeric) = (false, false);
+
+    let rows = fingerings
+        .into_iter()
+        .flat_map(|f| f.0.iter().zip(&f.1))
+        .filter(|(row, _)| row.iter().any(|f| !f.is_unassigned()));
+
+    for (_, notation) in rows {
+        match notation {
+            RowNotation::Letters => letters = true,
+            RowNotation::Numeric => numeric = true,
+            RowNotation::Mixed => return Err(DErr::MixedFingerNotation.into()),
+        }
+    }
+
+    match (letters, numeric) {
+        (true, true) => Err(DErr::MixedFingerNotation.into()),
+        (false, true) => Ok(FingerNotation::Numeric),
+        _ => Ok(FingerNotation::Letters),
+    }
+}
+
+/// Get the length of the tap-hold key at the start of `s`, if it starts with one. Its parts may contain
+/// whitespace if they're quoted, so the key ends at the first whitespace where everything before it is a
+/// valid tap-hold.
+fn tap_hold_len(s: &str) -> Option<usize> {
+    if !s.starts_with("tap(") {
+        return None;
+    }
+
+    s.char_indices()
+        .filter(|(_, c)| c.is_whitespace())
+        .map(|(i, _)| i)
+        .chain([s.len()])
+        .find(|&end| is_tap_hold_token(&s[..end]))
+}
+
+/// Check if `s` is a tap-hold key where every part that contains whitespace is either quoted or a
+/// tap-hold of its own.
+fn is_tap_hold_token(s: &str) -> bool {
+    let is_part = |part: &str| {
+        !part.contains(char::is_whitespace)
+            || quoted_len(part) == Some(part.len())
+            || is_tap_hold_token(part)
+    };
+
+    split_tap_hold(s).is_some_and(|(tap, hold)| is_part(tap) && is_part(hold))
+}
+
+/// Get the length of the quoted key at the start of `s`, quotes included, if it starts with one. Quotes
+/// and whitespace escaped with a backslash don't end the key.
+fn quoted_len(s: &str) -> Option<usize> {
+    let inner = s.strip_prefix('"')?;
+    let mut chars = inner.char_indices().peekable();
+    // Treating the start like whitespace means a quote right after the opening one doesn't close the key.
+    let mut after_whitespace = true;
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if chars
+                .peek()
+                .is_some_and(|&(_, next)| is_quote_escapable(next)) =>
+            {
+                chars.next();
+                after_whitespace = false;
+            }
+            c if c.is_whitespace() && i == 0 => return None,
+            '"' if !after_whitespace
+                && inner[i + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace) =>
+            {
+                return Some(i + 2)
+            }
+            c => after_whitespace = c.is_whitespace(),
+        }
+    }
+
+    None
+}
+
+/// Optional information about a layer that doesn't affect what it outputs, but can be useful for tools like
+/// layout editors.
+#[skip_serializing_none]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LayerMetadata {
+    /// Color the layer should be displayed with, like `#fff`.
+    pub color: Option<String>,
+    /// Description of what the layer is for.
+    pub description: Option<String>,
+}
+
+impl LayerMetadata {
+    /// Check if no metadata is present.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The way a [`Layer`](crate::Layer) is represented in a .dof file.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum LayerRepr {
+    Rows(#[serde_as(as = "Vec<LayerStrAsRow>")] Vec<Vec<Key>>),
+    Object {
+        #[serde_as(as = "Vec<LayerStrAsRow>")]
+        keys: Vec<Vec<Key>>,
+        #[serde(flatten)]
+        metadata: LayerMetadata,
+    },
+}
+
+impl From<LayerRepr> for Layer {
+    fn from(repr: LayerRepr) -> Self {
+        match repr {
+            LayerRepr::Rows(keys) => Self(keys, LayerMetadata::default()),
+            LayerRepr::Object { keys, metadata } => Self(keys, metadata),
+        }
     }
 }
 
-keyboard_conv!(Layer, Key, LayerStrAsRow);
+impl From<Layer> for LayerRepr {
+    fn from(layer: Layer) -> Self {
+        match layer {
+            Layer(keys, metadata) if metadata.is_empty() => LayerRepr::Rows(keys),
+            Layer(keys, metadata) => LayerRepr::Object { keys, metadata },
+        }
+    }
+}
 
 /// An anchor represents where the top left key on a `Dof` is compared to where it would be on a physical
 /// keyboard. For example, if you were to provide a 3x10 raster of letters but would like this applied to an
@@ -532,6 +1283,7 @@ keyboard_conv!(Layer, Key, LayerStrAsRow);
 /// is) would need to be shifted one left and one up to be in the top left corner of the physical keyboard.
 /// Therefore, the default value of an anchor is dependent on the physical keyboard it is applied to.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Anchor(u8, u8);
 
 impl Anchor {
@@ -551,6 +1303,26 @@ impl Anchor {
     }
 }
 
+/// Abstraction over the way an actual .dof file is allowed to represent the anchor, being either explicit
+/// through providing its coordinate, or by providing a character on the main layer, in which case the
+/// position of that character on the main layer is used. Either way, it is serialized back as a coordinate
+/// when converting from a [`Dof`](crate::Dof).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum ParsedAnchor {
+    /// Covers the case where the anchor is specified as a coordinate, like `[1, 1]`
+    Position(Anchor),
+    /// Covers the case where the anchor is specified as a character on the main layer, like `"q"`
+    Char(char),
+}
+
+impl From<Anchor> for ParsedAnchor {
+    fn from(a: Anchor) -> Self {
+        Self::Position(a)
+    }
+}
+
 /// A Key with metadata attached. These are produced by calling [`Dof::keys()`](crate::Dof::keys()).
 #[derive(Clone, Debug, PartialEq)]
 pub struct DescriptiveKey<'a> {
@@ -629,7 +1401,7 @@ impl<'a> DescriptiveKey<'a> {
 
     /// Check if the key is on any of the provided fingers.
     pub fn is_on_fingers(&self, fingers: &[Finger]) -> bool {
-        fingers.iter().any(|f| self.finger == *f)
+        fingers.contains(&self.finger)
     }
 
     /// Check if the key is on left hand, including left thumb.
@@ -700,8 +1472,10 @@ impl<'a> DescriptiveKey<'a> {
 #[serde_as]
 #[skip_serializing_none]
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DofIntermediate {
     pub name: String,
+    pub version: Option<String>,
     pub authors: Option<Vec<String>>,
     // #[serde_as(as = "DisplayFromStr")]
     // pub board: KeyboardType,
@@ -710,57 +1484,704 @@ pub struct DofIntermediate {
     pub description: Option<String>,
     pub languages: Option<Vec<Language>>,
     pub link: Option<String>,
-    pub layers: BTreeMap<String, Layer>,
-    pub anchor: Option<Anchor>,
+    #[serde(default, skip_serializing_if = "no_tags")]
+    pub tags: Option<Vec<String>>,
+    pub corpus: Option<String>,
+    pub layers: LayerMap,
+    pub base_layer: Option<String>,
+    pub anchor: Option<ParsedAnchor>,
+    pub geometry: Option<Geometry>,
+    pub key_ids: Option<Vec<Vec<u16>>>,
+    pub thumbs: Option<Layer>,
+    pub thumb_fingering: Option<Fingering>,
+    pub auto_shift: Option<bool>,
+    pub case_insensitive_layers: Option<bool>,
     // pub alt_fingerings: Option<Vec<String>>,
     // pub combos: Option<HashMap<String, String>>,
+    #[serde(default, deserialize_with = "deserialize_fingering")]
     pub fingering: Option<ParsedFingering>,
+    pub fingerings: Option<BTreeMap<String, ParsedFingering>>,
+    pub locked: Option<Vec<Anchor>>,
+    pub checksum: Option<String>,
+}
+
+/// Fingering used when a layout doesn't provide one.
+static DEFAULT_FINGERING: ParsedFingering = ParsedFingering::Implicit(NamedFingering::Traditional);
+
+fn no_tags(tags: &Option<Vec<String>>) -> bool {
+    tags.as_ref().is_none_or(Vec::is_empty)
 }
 
 impl DofIntermediate {
-    /// Get the main layer if it exists. If it doesn't return a `NoMainLayer` error.
+    /// Get the main layer, being the layer named by [`base_layer_name()`](crate::DofIntermediate::base_layer_name).
+    /// If it doesn't exist return a `NoMainLayer` error.
     pub fn main_layer(&self) -> Result<&Layer> {
-        self.layers.get("main").ok_or(DErr::NoMainLayer.into())
+        self.base_layer_name()
+            .and_then(|name| self.layers.get(name))
+            .ok_or(DErr::NoMainLayer.into())
     }
 
-    /// If not provided, will generate a default shift layer with some sane defaults. This is useful
-    /// if your shift layer isn't doing anything special. The defaults are:
-    /// * Letters are uppercased, unless their uppercase version spans multiple characters,
-    /// * Symbols and numbers are given their qwerty uppercase. This means that `7` becomes `&`, `'`
-    /// becomes `"`, `[` becomes `{`, etc,
-    /// * Special keys become Transparent.
-    ///
-    /// **Words are unaffected!** This means that if you would like Word keys to output something different,
-    /// you must specify a custom shift layer.
-    pub fn generate_shift_layer(main: &Layer) -> Layer {
-        main.0
-            .iter()
-            .map(|row| row.iter().map(|k| k.shifted()).collect::<Vec<_>>())
-            .collect::<Vec<_>>()
-            .into()
+    /// Get the name of the base layer, being the layer other layers are validated against and that
+    /// transparent keys refer to. This is `base_layer` if provided, otherwise `main` if it exists, and
+    /// otherwise the first layer in the [`LayerMap`](crate::LayerMap).
+    pub fn base_layer_name(&self) -> Option<&str> {
+        match &self.base_layer {
+            Some(name) => Some(name),
+            None if self.layers.contains_key("main") => Some("main"),
+            None => self.layers.keys().next().map(String::as_str),
+        }
     }
 
-    /// Validation check to see if the layers the [`Key::Layer`](crate::dofinitions::Key::Layer)
-    /// keys point to layers that actually exist.
-    pub fn validate_layer_keys(&self, main: &Layer) -> Result<()> {
-        let layers_dont_exist = main
-            .keys()
-            .filter_map(|k| match k {
-                Key::Layer { name: n } if !self.layers.contains_key(n) => Some(n.clone()),
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        if layers_dont_exist.is_empty() {
-            Ok(())
-        } else {
-            Err(DErr::LayersNotFound(layers_dont_exist).into())
+    /// Get the anchor of the layout. If no anchor is provided, the default anchor of the board is used,
+    /// and if the anchor is a character, its position on the main layer is used. If that character
+    /// doesn't exist on the main layer, an `AnchorCharNotFound` error is returned.
+    pub fn resolved_anchor(&self) -> Result<Anchor> {
+        match self.anchor {
+            None => Ok(self.board.anchor()),
+            Some(ParsedAnchor::Position(a)) => Ok(a),
+            Some(ParsedAnchor::Char(c)) => self
+                .main_layer()?
+                .rows()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    row.iter()
+                        .position(|k| k == &Key::Char(c))
+                        .map(|x| Anchor::new(x as u8, y as u8))
+                })
+                .ok_or(DErr::AnchorCharNotFound(c).into()),
         }
     }
 
-    /// Validation check to see if all layers are the same shape as the main layer.
-    pub fn validate_layer_shapes(&self, main: &Layer) -> Result<()> {
-        let main_shape = main.shape();
+    /// Get the geometry of the layout. An explicitly provided geometry is preferred, otherwise it is
+    /// derived from the physical keyboard, resized to the shape of the main layer.
+    pub fn resolved_geometry(&self) -> Result<Geometry> {
+        if let Some(geometry) = &self.geometry {
+            return Ok(geometry.clone());
+        }
+
+        let anchor = self.resolved_anchor()?;
+        let board = PhysicalKeyboard::try_from(self.board.clone())?
+            .resized(anchor, self.main_layer()?.shape())?
+            .into();
+
+        Ok(Geometry::from(&board))
+    }
+
+    /// Get the tags of the layout trimmed, lowercase and deduplicated, keeping the order they were first
+    /// written in. Empty tags are removed.
+    pub fn normalized_tags(&self) -> Vec<String> {
+        let mut tags = Vec::<String>::new();
+
+        for tag in self.tags.iter().flatten() {
+            let tag = tag.trim().to_lowercase();
+
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        tags
+    }
+
+    /// Get the identifier of the corpus the layout declares it was analyzed against, if any, so the
+    /// published statistics of the layout can be reproduced with the same corpus.
+    pub fn declared_corpus(&self) -> Option<&str> {
+        self.corpus.as_deref()
+    }
+
+    /// Get the notation the fingering is written in, across `fingering`, `thumb_fingering` and every
+    /// fingering in `fingerings`. Explicit fingerings are written with either letters or numbers, and a
+    /// `MixedFingerNotation` error is returned if both are used anywhere. If none of the fingerings are
+    /// explicit, [`FingerNotation::Named`](crate::dofinitions::FingerNotation::Named) is returned, while a
+    /// fingering that is left empty on purpose returns a `NoFingering` error.
+    pub fn fingering_notation(&self) -> Result<FingerNotation> {
+        if self.fingering == Some(ParsedFingering::None) {
+            return Err(DErr::NoFingering.into());
+        }
+
+        let explicit = self
+            .fingering
+            .iter()
+            .chain(self.fingerings.iter().flat_map(|f| f.values()))
+            .filter_map(|f| match f {
+                ParsedFingering::Explicit(f) => Some(f),
+                _ => None,
+            })
+            .chain(&self.thumb_fingering)
+            .collect::<Vec<_>>();
+
+        match explicit.is_empty() {
+            true => Ok(FingerNotation::Named),
+            false => notation_of(explicit),
+        }
+    }
+
+    /// Get the fingering of a layer as rows of fingers written as numbers, where `LP` is 0 up to `RP`
+    /// being 9, separated by spaces. Keys without a finger are written as `-`. This is the numeric
+    /// counterpart to the letters a fingering is serialized with by default.
+    pub fn fingering_numeric_string(&self, layer: &str) -> Result<Vec<String>> {
+        let fingering = self.explicit_fingering_for(layer)?;
+
+        let rows = fingering
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&f| match f {
+                        Finger::Unassigned => f.to_string(),
+                        f => (f as u8).to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect();
+
+        Ok(rows)
+    }
+
+    /// Get the fingering of a specific layer. This is the fingering provided for that layer in
+    /// `fingerings` if there is one, and otherwise the fingering of the whole layout, defaulting to
+    /// `traditional` if that isn't provided either. Returns a `LayerDoesntExist` error if the layer
+    /// doesn't exist.
+    pub fn fingering_for(&self, layer: &str) -> Result<&ParsedFingering> {
+        self.layer_or_err(layer)?;
+
+        let fingering = self
+            .fingerings
+            .as_ref()
+            .and_then(|f| f.get(layer))
+            .or(self.fingering.as_ref())
+            .unwrap_or(&DEFAULT_FINGERING);
+
+        Ok(fingering)
+    }
+
+    /// Get the firmware id of the physical key at a position of the layers, like its index in the scan
+    /// matrix. Returns `None` if no key ids are provided or the position doesn't exist.
+    pub fn key_id_at(&self, row: usize, col: usize) -> Option<u16> {
+        self.key_ids.as_ref()?.get(row)?.get(col).copied()
+    }
+
+    /// Check if the key at a position of the layers is locked, meaning transformations like
+    /// [`mirror()`](crate::DofIntermediate::mirror) and
+    /// [`remap_order()`](crate::DofIntermediate::remap_order) leave it where it is. Locked positions are
+    /// provided as `[x, y]`, being the column and row of the key.
+    pub fn is_locked(&self, row: usize, col: usize) -> bool {
+        self.locked
+            .iter()
+            .flatten()
+            .any(|a| a.x() == col && a.y() == row)
+    }
+
+    /// Parse a layout that may be incomplete, filling in every field it leaves out from `template`, like
+    /// the board, anchor or fingering. Fields provided in `src` always win. Layers are merged by name, so
+    /// `src` only needs to contain the layers that differ from the template. This allows defining a family
+    /// of layouts as small differences from a single base layout.
+    pub fn from_str_with_template(
+        src: &str,
+        template: &DofIntermediate,
+    ) -> Result<DofIntermediate> {
+        use serde_json::Value;
+
+        let Value::Object(src) = serde_json::from_str(src)? else {
+            return Err(DErr::Json("expected the layout to be a JSON object".into()).into());
+        };
+
+        // A `DofIntermediate` always serializes to an object.
+        let Value::Object(mut merged) = serde_json::to_value(template)? else {
+            unreachable!()
+        };
+
+        for (field, value) in src {
+            match (merged.get_mut(&field), value) {
+                (Some(Value::Object(layers)), Value::Object(src_layers)) if field == "layers" => {
+                    layers.extend(src_layers)
+                }
+                (_, value) => {
+                    merged.insert(field, value);
+                }
+            }
+        }
+
+        let mut merged: DofIntermediate = serde_json::from_value(Value::Object(merged))?;
+        merged.refresh_checksum();
+
+        Ok(merged)
+    }
+
+    /// Serialize the layout to compact JSON that contains as few fields as possible. Absent fields are
+    /// left out, as are fields equal to their default, like an anchor that matches the board, a base layer
+    /// that would be picked anyway or English as the only language. An explicit fingering is collapsed
+    /// to the name of a known fingering if it matches that fingering exactly.
+    pub fn to_minimal_json(&self) -> Result<String> {
+        let mut minimal = self.clone();
+
+        if matches!(minimal.anchor, Some(ParsedAnchor::Position(a)) if a == self.board.anchor()) {
+            minimal.anchor = None;
+        }
+
+        if matches!(minimal.languages.as_deref(), Some([lang]) if lang == &Language::default()) {
+            minimal.languages = None;
+        }
+
+        if minimal.base_layer.is_some() {
+            let base_layer = minimal.base_layer.take();
+
+            if minimal.base_layer_name() != base_layer.as_deref() {
+                minimal.base_layer = base_layer;
+            }
+        }
+
+        if let Some(ParsedFingering::Explicit(f)) = &self.fingering {
+            if let Some(named) = self.known_fingering(f) {
+                minimal.fingering = Some(ParsedFingering::Implicit(named));
+            }
+        }
+
+        minimal.refresh_checksum();
+
+        serde_json::to_string(&minimal).map_err(Into::into)
+    }
+
+    /// Convert a layout on an ANSI board to one on an ISO board. The key on the ANSI backslash position
+    /// moves down next to the home row, where ISO has an extra key, and enter takes its place at the end
+    /// of the top letter row. An empty key is inserted at the extra ISO position next to left shift.
+    /// Explicit fingerings, including those in `fingerings`, are reshaped along with the layers, using `LP`
+    /// for the key next to left shift and `RP` for a key added to the home row. Key ids and geometry describe the physical board, so they're
+    /// left out of the result.
+    ///
+    /// Returns an `UnsupportedIsoConversion` error if the layout isn't on an ANSI board or its anchor lies
+    /// to the right of the extra ISO key.
+    pub fn to_iso(&self) -> Result<DofIntermediate> {
+        self.convert_iso(KeyboardType::Ansi, KeyboardType::Iso)
+    }
+
+    /// Convert a layout on an ISO board to one on an ANSI board, reversing
+    /// [`to_iso()`](crate::DofIntermediate::to_iso). The key next to the ISO enter swaps places with it,
+    /// and the extra ISO key next to left shift is removed, along with its finger in explicit
+    /// fingerings.
+    ///
+    /// Returns an `UnsupportedIsoConversion` error if the layout isn't on an ISO board or its anchor lies
+    /// to the right of the extra ISO key.
+    pub fn to_ansi(&self) -> Result<DofIntermediate> {
+        self.convert_iso(KeyboardType::Iso, KeyboardType::Ansi)
+    }
+
+    fn convert_iso(&self, from: KeyboardType, to: KeyboardType) -> Result<DofIntermediate> {
+        let anchor = self.resolved_anchor()?;
+
+        if self.board != ParseKeyboard::Named(from.clone()) || anchor.x() > 1 {
+            return Err(DErr::UnsupportedIsoConversion(from).into());
+        }
+
+        let to_iso = to == KeyboardType::Iso;
+        let mut converted = self.clone();
+
+        // The ansi enter key moves to the end of the top letter row, where the tall iso enter lives.
+        let iso_enter = 1usize
+            .checked_sub(anchor.y())
+            .map(|row| (row, 13 - anchor.x()));
+
+        for layer in converted.layers.values_mut() {
+            let enter = Key::Special(SpecialKey::Enter);
+
+            if !to_iso {
+                for key in layer.0.iter_mut().flatten() {
+                    if *key == Key::Special(SpecialKey::IsoEnter) {
+                        *key = enter.clone();
+                    }
+                }
+            }
+
+            reshape_iso(
+                &mut layer.0,
+                anchor,
+                to_iso,
+                true,
+                enter.clone(),
+                Key::Empty,
+            );
+
+            if let (true, Some((row, col))) = (to_iso, iso_enter) {
+                if let Some(key) = layer.0.get_mut(row).and_then(|r| r.get_mut(col)) {
+                    if *key == enter {
+                        *key = Key::Special(SpecialKey::IsoEnter);
+                    }
+                }
+            }
+        }
+
+        let explicit = converted
+            .fingering
+            .iter_mut()
+            .chain(converted.fingerings.iter_mut().flat_map(|f| f.values_mut()));
+
+        for fingering in explicit {
+            if let ParsedFingering::Explicit(f) = fingering {
+                reshape_iso(&mut f.0, anchor, to_iso, false, Finger::RP, Finger::LP);
+            }
+        }
+
+        converted.board = ParseKeyboard::Named(to);
+        converted.key_ids = None;
+        converted.geometry = None;
+        converted.refresh_checksum();
+
+        Ok(converted)
+    }
+
+    /// Move the layout from a `from` board onto a `to` board with a different order of keys, like from a
+    /// row-staggered to a column-staggered board. `mapping` contains the position on the new board, as
+    /// `(row, col)`, of every key of a layer in row-major order. Every layer is remapped, as is the
+    /// fingering, which is made explicit so every finger stays with its key. Positions of the new board
+    /// that no key maps to are left empty and unfingered. Keys at [locked](crate::DofIntermediate::is_locked)
+    /// positions stay where they are, and the entries of `mapping` for them are ignored. The anchor, key
+    /// ids and geometry describe the old board, so they're left out of the result.
+    ///
+    /// Returns an `UnexpectedBoard` error if the layout isn't on the `from` board, and an `InvalidRemapping`
+    /// error if a layer doesn't have exactly one key for every entry in `mapping`, or if two keys map to
+    /// the same position, including a locked one.
+    pub fn remap_order(
+        &self,
+        from: KeyboardType,
+        to: KeyboardType,
+        mapping: &[(usize, usize)],
+    ) -> Result<DofIntermediate> {
+        if self.board != ParseKeyboard::Named(from.clone()) {
+            return Err(DErr::UnexpectedBoard(from).into());
+        }
+
+        let mut converted = self.clone();
+        let locked = |row, col| self.is_locked(row, col);
+
+        let fingering = self.explicit_fingering(self.main_layer()?)?;
+        converted.fingering = Some(ParsedFingering::Explicit(
+            remap_rows(&fingering.0, mapping, &locked, Finger::Unassigned)?.into(),
+        ));
+
+        for (name, fingering) in converted.fingerings.iter_mut().flatten() {
+            let explicit = self.explicit_fingering_for(name)?;
+            *fingering = ParsedFingering::Explicit(
+                remap_rows(&explicit.0, mapping, &locked, Finger::Unassigned)?.into(),
+            );
+        }
+
+        for layer in converted.layers.values_mut() {
+            layer.0 = remap_rows(&layer.0, mapping, &locked, Key::Empty)?;
+        }
+
+        converted.board = ParseKeyboard::Named(to);
+        converted.anchor = None;
+        converted.key_ids = None;
+        converted.geometry = None;
+        converted.refresh_checksum();
+
+        Ok(converted)
+    }
+
+    /// Mirror the layout horizontally, so every row of every layer is reversed and keys move to the other
+    /// hand. If a key is [locked](crate::DofIntermediate::is_locked), both it and the key it would swap
+    /// places with stay where they are. The fingering, key ids and geometry describe the physical board,
+    /// so they're left as is.
+    pub fn mirror(&self) -> DofIntermediate {
+        let mut mirrored = self.clone();
+
+        for layer in mirrored.layers.values_mut() {
+            for (row, keys) in layer.0.iter_mut().enumerate() {
+                let len = keys.len();
+
+                for col in 0..len / 2 {
+                    let other = len - 1 - col;
+
+                    if !self.is_locked(row, col) && !self.is_locked(row, other) {
+                        keys.swap(col, other);
+                    }
+                }
+            }
+        }
+
+        mirrored.refresh_checksum();
+
+        mirrored
+    }
+
+    /// Create a copy of the layout to derive a new layout from, with a new name and authors. Metadata that
+    /// belongs to the original layout is cleared, being its year and link, while the description is
+    /// replaced by a note saying which layout it was forked from. The checksum is cleared as well, as it
+    /// no longer matches the content. Everything else, like the layers and fingering, is kept as is.
+    pub fn fork(&self, new_name: &str, new_authors: Vec<String>) -> DofIntermediate {
+        let mut fork = self.clone();
+
+        fork.name = new_name.into();
+        fork.authors = Some(new_authors);
+        fork.year = None;
+        fork.link = None;
+        fork.description = Some(format!("Forked from {}", self.name));
+        fork.checksum = None;
+
+        fork
+    }
+
+    /// Compute the checksum again after the content of the layout changed, if it has one. Without the
+    /// `checksum` feature that isn't possible, and a checksum that might not match is left out instead.
+    fn refresh_checksum(&mut self) {
+        #[cfg(feature = "checksum")]
+        if self.checksum.is_some() {
+            self.checksum = Some(self.compute_checksum());
+            return;
+        }
+
+        self.checksum = None;
+    }
+
+    /// Find the known fingering that generates exactly `fingering` for the board and anchor of the
+    /// layout, if any.
+    fn known_fingering(&self, fingering: &Fingering) -> Option<NamedFingering> {
+        match &self.board {
+            ParseKeyboard::Named(board) => {
+                fingering.identify_at(board, self.resolved_anchor().ok()?)
+            }
+            _ => None,
+        }
+    }
+
+    /// Pad every layer to a common shape, being the longest length of each row across all layers, by
+    /// appending `fill` to rows that are too short, typically `Key::Transparent` or `Key::Empty`. Explicit
+    /// fingerings, including those in `fingerings`, are padded to the same shape with `Finger::Unassigned`.
+    /// There is no id to give a padded key, so `key_ids` are cleared if they don't match the new shape.
+    /// Returns the resulting shape.
+    pub fn pad_layers(&mut self, fill: Key) -> Vec<usize> {
+        let mut shape = Vec::<usize>::new();
+
+        for layer in self.layers.values() {
+            for (i, row) in layer.rows().enumerate() {
+                match shape.get_mut(i) {
+                    Some(len) => *len = (*len).max(row.len()),
+                    None => shape.push(row.len()),
+                }
+            }
+        }
+
+        for layer in self.layers.values_mut() {
+            layer.0.resize(shape.len(), Vec::new());
+
+            for (row, &len) in layer.0.iter_mut().zip(&shape) {
+                row.resize(len, fill.clone());
+            }
+        }
+
+        let explicit = self
+            .fingering
+            .iter_mut()
+            .chain(self.fingerings.iter_mut().flat_map(|f| f.values_mut()));
+
+        for fingering in explicit {
+            if let ParsedFingering::Explicit(f) = fingering {
+                f.pad_to_shape(&shape, Finger::Unassigned);
+            }
+        }
+
+        if self
+            .key_ids
+            .as_ref()
+            .is_some_and(|ids| !ids.iter().map(Vec::len).eq(shape.iter().copied()))
+        {
+            self.key_ids = None;
+        }
+
+        self.refresh_checksum();
+
+        shape
+    }
+
+    /// Get a layer by name. If it doesn't exist return a `LayerDoesntExist` error.
+    pub(crate) fn layer_or_err(&self, name: &str) -> Result<&Layer> {
+        self.layers
+            .get(name)
+            .ok_or(DErr::LayerDoesntExist(name.into()).into())
+    }
+
+    /// Get the fingering of a layer without checking it against the shape of the layer, where the
+    /// fingering is the one given by [`fingering_for()`](crate::DofIntermediate::fingering_for). An
+    /// explicit fingering is returned as is, while an implicit one is generated for the whole board and
+    /// cut off at the anchor, so rows may be longer than those of the layer.
+    pub(crate) fn unshaped_fingering_for(&self, layer: &str) -> Result<Fingering> {
+        match self.fingering_for(layer)? {
+            ParsedFingering::Explicit(f) => Ok(f.clone()),
+            ParsedFingering::Implicit(named) => {
+                let anchor = self.resolved_anchor()?;
+
+                let fingering = self
+                    .board
+                    .fingering(named)?
+                    .into_inner()
+                    .into_iter()
+                    .skip(anchor.y())
+                    .map(|row| row.into_iter().skip(anchor.x()).collect())
+                    .collect::<Vec<_>>();
+
+                Ok(fingering.into())
+            }
+            ParsedFingering::None => Err(DErr::NoFingering.into()),
+        }
+    }
+
+    /// If not provided, will generate a default shift layer with some sane defaults. This is useful
+    /// if your shift layer isn't doing anything special. The defaults are:
+    /// * Letters are uppercased, unless their uppercase version spans multiple characters,
+    /// * Symbols and numbers are given their qwerty uppercase. This means that `7` becomes `&`, `'`
+    ///   becomes `"`, `[` becomes `{`, etc,
+    /// * Special keys become Transparent.
+    ///
+    /// **Words are unaffected!** This means that if you would like Word keys to output something different,
+    /// you must specify a custom shift layer.
+    pub fn generate_shift_layer(main: &Layer) -> Layer {
+        main.0
+            .iter()
+            .map(|row| row.iter().map(|k| k.shifted()).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Works like [`generate_shift_layer()`](crate::DofIntermediate::generate_shift_layer), but uppercases
+    /// letters using `casing` instead of the default Unicode uppercasing. This is useful for languages
+    /// where simple uppercasing is wrong, like Turkish, where `i` should become `İ`.
+    pub fn generate_shift_layer_with<F>(main: &Layer, casing: F) -> Layer
+    where
+        F: Fn(char) -> String,
+    {
+        main.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|k| k.shifted_with(&casing))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Generate the shift layer of a layout that sets `auto_shift`. This works like
+    /// [`generate_shift_layer()`](crate::DofIntermediate::generate_shift_layer), except that layer keys
+    /// become `Transparent` as well, as no key without a shifted version is repeated on the shift layer.
+    pub fn generate_auto_shift_layer(main: &Layer) -> Layer {
+        main.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|k| match k {
+                        Key::Layer { .. } => Key::Transparent,
+                        k => k.shifted(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Get a layer by name, ignoring case. An exact match is always preferred, after which the first
+    /// layer whose name matches case-insensitively is returned.
+    pub fn layer_ci(&self, name: &str) -> Option<&Layer> {
+        self.layers.get(name).or_else(|| {
+            let name = name.to_lowercase();
+
+            self.layers
+                .iter()
+                .find(|(n, _)| n.to_lowercase() == name)
+                .map(|(_, l)| l)
+        })
+    }
+
+    /// Validation check to see if the layers the [`Key::Layer`](crate::dofinitions::Key::Layer)
+    /// keys point to layers that actually exist.
+    pub fn validate_layer_keys(&self, main: &Layer) -> Result<()> {
+        self.validate_layer_keys_with(main, |name| self.layers.contains_key(name))
+    }
+
+    /// Validation check to see if the layers the [`Key::Layer`](crate::dofinitions::Key::Layer)
+    /// keys point to layers that actually exist, where layer names are matched case-insensitively.
+    pub fn validate_layer_keys_ci(&self, main: &Layer) -> Result<()> {
+        self.validate_layer_keys_with(main, |name| self.layer_ci(name).is_some())
+    }
+
+    fn validate_layer_keys_with(&self, main: &Layer, exists: impl Fn(&str) -> bool) -> Result<()> {
+        let layers_dont_exist = main
+            .keys()
+            .filter_map(|k| k.layer_target().filter(|n| !exists(n)).map(Into::into))
+            .collect::<Vec<_>>();
+
+        if layers_dont_exist.is_empty() {
+            Ok(())
+        } else {
+            Err(DErr::LayersNotFound(layers_dont_exist).into())
+        }
+    }
+
+    /// Validation check to see if every layer has a name, and if no two layer names only differ in
+    /// capitalization, like `Main` and `main`, which is almost certainly a mistake. Returns an error
+    /// containing every layer name that has a case-insensitive duplicate.
+    pub fn validate_layer_names(&self) -> Result<()> {
+        if self.layers.contains_key("") {
+            return Err(DErr::EmptyLayerName.into());
+        }
+
+        let duplicates = self
+            .layers
+            .keys()
+            .filter(|name| {
+                self.layers
+                    .keys()
+                    .any(|other| other != *name && other.to_lowercase() == name.to_lowercase())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if duplicates.is_empty() {
+            Ok(())
+        } else {
+            Err(DErr::LayerNamesDifferInCase(duplicates).into())
+        }
+    }
+
+    /// Get the optional version of the .dof format the layout targets.
+    pub fn format_version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Validation check to see if the version of the .dof format the layout targets, if any, isn't newer
+    /// than [`CURRENT_VERSION`](crate::CURRENT_VERSION). Versions are compared as `major.minor.patch`,
+    /// where missing parts are treated as 0.
+    pub fn validate_version(&self) -> Result<()> {
+        let Some(version) = &self.version else {
+            return Ok(());
+        };
+
+        let parse = |v: &str| {
+            v.split('.')
+                .map(|n| n.trim().parse::<u64>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .ok()
+                .filter(|parts| (1..=3).contains(&parts.len()))
+                .map(|mut parts| {
+                    parts.resize(3, 0);
+                    parts
+                })
+        };
+
+        // `CURRENT_VERSION` is always valid semver.
+        let current = parse(CURRENT_VERSION).unwrap();
+
+        match parse(version) {
+            None => Err(DErr::InvalidVersion(version.clone()).into()),
+            Some(v) if v > current => {
+                Err(DErr::NewerVersion(version.clone(), CURRENT_VERSION).into())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Validation check to see if all layers are the same shape as the main layer.
+    pub fn validate_layer_shapes(&self, main: &Layer) -> Result<()> {
+        let main_shape = main.shape();
 
         let incompatible_shapes = self
             .layers
@@ -777,31 +2198,475 @@ impl DofIntermediate {
         }
     }
 
+    /// Check if every row of a layer that sits on the 3x10 grid has the same length. Rows below the grid,
+    /// like the thumb row, are shorter by design and aren't checked. Returns false if the layer doesn't
+    /// exist or the anchor can't be resolved.
+    pub fn is_rectangular(&self, layer: &str) -> bool {
+        let (Some(layer), Ok(anchor)) = (self.layers.get(layer), self.resolved_anchor()) else {
+            return false;
+        };
+
+        let shape = layer.shape();
+        let grid_rows = shape.inner().len().min(3usize.saturating_sub(anchor.y()));
+
+        shape.inner()[..grid_rows].windows(2).all(|w| w[0] == w[1])
+    }
+
+    /// Validation check to see if the main layer is rectangular on boards that require it, being
+    /// `ortho`, following [`is_rectangular()`](crate::DofIntermediate::is_rectangular). Staggered boards
+    /// are allowed to have rows of different lengths. Since all layers are required to have the same
+    /// shape as the main layer, only the main layer is checked.
+    pub fn validate_rectangular(&self, main: &Layer) -> Result<()> {
+        if !matches!(self.board, ParseKeyboard::Named(KeyboardType::Ortho)) {
+            return Ok(());
+        }
+
+        self.resolved_anchor()?;
+
+        match self
+            .base_layer_name()
+            .is_some_and(|n| self.is_rectangular(n))
+        {
+            true => Ok(()),
+            false => Err(DErr::RaggedOrthoLayer(main.shape().into_inner()).into()),
+        }
+    }
+
+    /// Validation check to see if the anchor points at a key on the main layer that also has a finger
+    /// assigned in the fingering. Returns an `AnchorBiggerThanLayout` error if the main layer has no key at
+    /// the anchor, and an `AnchorNotFingered` error if the key has no finger. If `require_index` is set,
+    /// the anchored key must also be typed by an index finger, as is the convention for home positions,
+    /// otherwise an `AnchorNotOnIndex` error is returned.
+    pub fn validate_anchor(&self, require_index: bool) -> Result<()> {
+        let anchor = self.resolved_anchor()?;
+        let main = self.main_layer()?;
+
+        if main
+            .0
+            .get(anchor.y())
+            .and_then(|r| r.get(anchor.x()))
+            .is_none()
+        {
+            return Err(DErr::AnchorBiggerThanLayout.into());
+        }
+
+        let fingering = self.explicit_fingering(main)?;
+
+        match fingering.0.get(anchor.y()).and_then(|r| r.get(anchor.x())) {
+            None | Some(Finger::Unassigned) => Err(DErr::AnchorNotFingered(anchor).into()),
+            Some(&f) if require_index && !f.is_index() => {
+                Err(DErr::AnchorNotOnIndex(anchor, f).into())
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Validation check to see if the key ids, if provided, have the same shape as the main layer.
+    pub fn validate_key_ids(&self, main: &Layer) -> Result<()> {
+        match &self.key_ids {
+            Some(ids)
+                if Shape::from(ids.iter().map(Vec::len).collect::<Vec<_>>()) != main.shape() =>
+            {
+                Err(DErr::IncompatibleKeyIdsShape.into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validation check to see if every locked position, if provided, points at a key of the main layer.
+    /// Returns an error containing every position that doesn't.
+    pub fn validate_locked(&self, main: &Layer) -> Result<()> {
+        let outside = self
+            .locked
+            .iter()
+            .flatten()
+            .filter(|a| main.0.get(a.y()).and_then(|r| r.get(a.x())).is_none())
+            .copied()
+            .collect::<Vec<_>>();
+
+        match outside.is_empty() {
+            true => Ok(()),
+            false => Err(DErr::LockedPositionsNotOnLayout(outside).into()),
+        }
+    }
+
+    /// Validation check to see if the thumb fingering, if provided, has the same shape as the thumb
+    /// cluster. The thumb cluster itself is validated independently of the main layer, so it can have
+    /// any shape.
+    pub fn validate_thumbs(&self) -> Result<()> {
+        match (&self.thumbs, &self.thumb_fingering) {
+            (Some(thumbs), Some(f)) if thumbs.shape() != f.shape() => {
+                Err(DErr::IncompatibleThumbFingeringShape.into())
+            }
+            (None, Some(_)) => Err(DErr::IncompatibleThumbFingeringShape.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validation check to see if positions that hold a modifier on the base layer, being a
+    /// [`Key::Special`](crate::dofinitions::Key::Special) or [`Key::Layer`](crate::dofinitions::Key::Layer)
+    /// key, don't hold a character or word on the shift layer, which is likely a mistake. Returns an error
+    /// containing every offending position.
+    pub fn check_shift_layer(&self, base: &str, shift: &str) -> Result<()> {
+        let base = self.layer_or_err(base)?;
+        let shift = self.layer_or_err(shift)?;
+
+        let mismatches = base
+            .rows()
+            .zip(shift.rows())
+            .enumerate()
+            .flat_map(|(row, (base_row, shift_row))| {
+                base_row
+                    .iter()
+                    .zip(shift_row)
+                    .enumerate()
+                    .filter(|(_, (b, s))| {
+                        (b.is_special() || b.is_layer()) && (s.is_char() || s.is_word())
+                    })
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect::<Vec<_>>();
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(DErr::ShiftedModifiers(mismatches).into())
+        }
+    }
+
+    /// Validation check to see if every layer other than `base` keeps the modifier skeleton of `base`,
+    /// following the convention that layers only change the alpha and symbol area. Wherever `base` holds
+    /// a [`Key::Special`](crate::dofinitions::Key::Special) or [`Key::Layer`](crate::dofinitions::Key::Layer)
+    /// key, every other layer must hold either the same key or a transparent one. Returns an error
+    /// containing the layer name and position of every key that overrides the skeleton.
+    pub fn validate_layer_skeleton(&self, base: &str) -> Result<()> {
+        let base_layer = self.layer_or_err(base)?;
+
+        let overrides = self
+            .layers
+            .iter()
+            .filter(|(name, _)| name.as_str() != base)
+            .flat_map(|(name, layer)| {
+                base_layer.rows().zip(layer.rows()).enumerate().flat_map(
+                    move |(row, (base_row, row_keys))| {
+                        base_row
+                            .iter()
+                            .zip(row_keys)
+                            .enumerate()
+                            .filter(|(_, (b, k))| {
+                                (b.is_special() || b.is_layer()) && !k.is_transparent() && b != k
+                            })
+                            .map(move |(col, _)| (name.clone(), row, col))
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if overrides.is_empty() {
+            Ok(())
+        } else {
+            Err(DErr::SkeletonOverridden(overrides).into())
+        }
+    }
+
+    /// Validation check to see if every [`Key::Char`](crate::dofinitions::Key::Char) on the layers is a
+    /// printable character, as control characters and whitespace end up as invisible keys that are easy
+    /// to produce by accident when copy-pasting. Space should be written as
+    /// [`SpecialKey::Space`](crate::dofinitions::SpecialKey::Space) instead. Returns an error containing
+    /// the layer name, position and codepoint of every character key that isn't printable.
+    pub fn validate_printable(&self) -> Result<()> {
+        let unprintable = self
+            .layers
+            .iter()
+            .flat_map(|(name, layer)| {
+                layer.rows().enumerate().flat_map(move |(row, keys)| {
+                    keys.iter()
+                        .enumerate()
+                        .filter_map(move |(col, key)| match key {
+                            Key::Char(c) if c.is_control() || c.is_whitespace() => {
+                                Some((name.clone(), row, col, *c as u32))
+                            }
+                            _ => None,
+                        })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if unprintable.is_empty() {
+            Ok(())
+        } else {
+            Err(DErr::UnprintableChars(unprintable).into())
+        }
+    }
+
+    /// Check if the amount of keys on a layer is plausible for the board of the layout, which helps to
+    /// catch layouts that are assigned the wrong board. Returns the amount of keys on the layer and the
+    /// [`slot_count()`](crate::dofinitions::KeyboardType::slot_count) of the board if the layer has more
+    /// keys than the board, or fewer than a third of them. Returns `None` if the amount is plausible or
+    /// the board doesn't have a fixed size.
+    pub fn check_slot_count(&self, layer: &str) -> Result<Option<(usize, usize)>> {
+        let keys = self.layer_or_err(layer)?.keys().count();
+
+        let slots = match &self.board {
+            ParseKeyboard::Named(board) => board.slot_count(),
+            _ => None,
+        };
+
+        Ok(slots
+            .filter(|&slots| keys > slots || keys * 3 < slots)
+            .map(|slots| (keys, slots)))
+    }
+
+    /// Check if characters on the base layer shift to the character `map` says they should, like `a` to `A`.
+    /// Returns `(row, col, base, shift)` for every position where the base layer holds a character in
+    /// `map` but the shift layer holds a different character. Positions with characters that aren't in
+    /// `map`, or that don't hold a character on the shift layer, are not checked. Because the map is
+    /// provided by the caller, this also works for layouts made for languages other than English.
+    pub fn check_shift_consistency(
+        &self,
+        base: &str,
+        shift: &str,
+        map: &BTreeMap<char, char>,
+    ) -> Result<Vec<(usize, usize, char, char)>> {
+        let base = self.layer_or_err(base)?;
+        let shift = self.layer_or_err(shift)?;
+
+        let mismatches = base
+            .rows()
+            .zip(shift.rows())
+            .enumerate()
+            .flat_map(|(row, (base_row, shift_row))| {
+                base_row
+                    .iter()
+                    .zip(shift_row)
+                    .enumerate()
+                    .filter_map(move |(col, keys)| match keys {
+                        (Key::Char(b), Key::Char(s)) if map.get(b).is_some_and(|e| e != s) => {
+                            Some((row, col, *b, *s))
+                        }
+                        _ => None,
+                    })
+            })
+            .collect();
+
+        Ok(mismatches)
+    }
+
+    /// Find characters that can be typed from more than one position across the given layers, like `1`
+    /// being on both the number row and a symbol layer. Returns each such character, sorted, with every
+    /// `(layer, row, col)` it is found at, in the order of `layers` and then row-major order.
+    #[allow(clippy::type_complexity)]
+    pub fn check_reachability_uniqueness(
+        &self,
+        layers: &[&str],
+    ) -> Result<Vec<(char, Vec<(String, usize, usize)>)>> {
+        let mut positions = BTreeMap::<char, Vec<(String, usize, usize)>>::new();
+
+        for &name in layers {
+            let layer = self.layer_or_err(name)?;
+
+            for (row, key_row) in layer.rows().enumerate() {
+                for (col, key) in key_row.iter().enumerate() {
+                    if let Key::Char(c) = key {
+                        positions
+                            .entry(*c)
+                            .or_default()
+                            .push((name.to_owned(), row, col));
+                    }
+                }
+            }
+        }
+
+        let duplicates = positions
+            .into_iter()
+            .filter(|(_, positions)| positions.len() > 1)
+            .collect();
+
+        Ok(duplicates)
+    }
+
+    /// Validation check to see if every key on a layer has exactly one finger assigned to it, by comparing
+    /// the layer and the fingering position by position. Returns an error containing the first position
+    /// where they diverge. This is the strict counterpart to
+    /// [`key_finger_pairs_lossy()`](crate::DofIntermediate::key_finger_pairs_lossy).
+    pub fn assert_one_finger_per_key(&self, layer: &str) -> Result<()> {
+        let fingering = match self.fingering_for(layer)? {
+            ParsedFingering::Explicit(f) => f.clone(),
+            _ => self.explicit_fingering_for(layer)?,
+        };
+        let layer = self.layer_or_err(layer)?;
+
+        let (key_rows, finger_rows) = (layer.inner(), fingering.inner());
+
+        for row in 0..key_rows.len().max(finger_rows.len()) {
+            match (key_rows.get(row), finger_rows.get(row)) {
+                (Some(k), Some(f)) if k.len() == f.len() => continue,
+                (Some(k), Some(f)) => {
+                    return Err(DErr::FingerCountMismatch(row, k.len().min(f.len())).into())
+                }
+                _ => return Err(DErr::FingerCountMismatch(row, 0).into()),
+            }
+        }
+
+        Ok(())
+    }
+
     /// Validation check to see if the provided fingering has the same shape as the main layer.
     /// If left implicit (by leaving just a name of a fingering, like `traditional` or `angle`)
     /// will try to generate a fingering with the same shape as the main layer.
     pub fn explicit_fingering(&self, main: &Layer) -> Result<Fingering> {
-        use ParsedFingering::*;
+        let fingering = self.fingering.as_ref().unwrap_or(&DEFAULT_FINGERING);
 
-        let d = Default::default();
-        let fingering = match &self.fingering {
-            Some(f) => f,
-            None => &d,
-        };
+        self.shaped_fingering(fingering, main)
+    }
+
+    /// Like [`explicit_fingering()`](crate::DofIntermediate::explicit_fingering), but for the fingering
+    /// of a specific layer as returned by [`fingering_for()`](crate::DofIntermediate::fingering_for),
+    /// checked against or generated for the shape of that layer.
+    pub fn explicit_fingering_for(&self, layer: &str) -> Result<Fingering> {
+        let fingering = self.fingering_for(layer)?;
+
+        self.shaped_fingering(fingering, self.layer_or_err(layer)?)
+    }
+
+    fn shaped_fingering(&self, fingering: &ParsedFingering, layer: &Layer) -> Result<Fingering> {
+        use ParsedFingering::*;
 
         match fingering {
-            Explicit(f) if f.shape() == main.shape() => Ok(f.clone()),
+            Explicit(f) if f.shape() == layer.shape() => Ok(f.clone()),
             Explicit(_) => Err(DErr::IncompatibleFingeringShape.into()),
             Implicit(named) => {
                 let fingering = self.board.fingering(named)?;
 
-                let anchor = match self.anchor {
-                    Some(a) => a,
-                    None => self.board.anchor(),
+                let anchor = self.resolved_anchor()?;
+
+                fingering.resized(anchor, layer.shape()).map(Into::into)
+            }
+            None => Err(DErr::NoFingering.into()),
+        }
+    }
+}
+
+/// Split `rows` into the items typed by the left and the right hand according to `fingers`, see
+/// [`Fingering::by_hand()`](crate::Fingering::by_hand).
+fn split_by_hand<T: Clone>(
+    rows: &[Vec<T>],
+    fingers: &[Vec<Finger>],
+    thumbs: Option<Hand>,
+) -> (Vec<Vec<T>>, Vec<Vec<T>>) {
+    let mut left = vec![Vec::new(); rows.len()];
+    let mut right = vec![Vec::new(); rows.len()];
+
+    for (i, (row, fingers)) in rows.iter().zip(fingers).enumerate() {
+        for (item, finger) in row.iter().zip(fingers) {
+            let hand = match thumbs {
+                Some(hand) if finger.is_thumb() => Some(hand),
+                _ => finger.hand(),
+            };
+
+            match hand {
+                Some(Hand::Left) => left[i].push(item.clone()),
+                Some(Hand::Right) => right[i].push(item.clone()),
+                None => {}
+            }
+        }
+    }
+
+    (left, right)
+}
+
+/// Move every item of `rows`, in row-major order, to the position `mapping` has for it, see
+/// [`remap_order()`](crate::DofIntermediate::remap_order). Items at positions that are `locked` stay where
+/// they are. Positions no item maps to are filled with `fill`.
+fn remap_rows<T: Clone>(
+    rows: &[Vec<T>],
+    mapping: &[(usize, usize)],
+    locked: &impl Fn(usize, usize) -> bool,
+    fill: T,
+) -> Result<Vec<Vec<T>>> {
+    let invalid = || DofError::from(DErr::InvalidRemapping(mapping.len()));
+
+    if rows.iter().map(Vec::len).sum::<usize>() != mapping.len() {
+        return Err(invalid());
+    }
+
+    let mut remapped = Vec::<Vec<Option<T>>>::new();
+
+    let items = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row, items)| items.iter().enumerate().map(move |(col, i)| (row, col, i)));
+
+    for ((from_row, from_col, item), &to) in items.zip(mapping) {
+        let (row, col) = match locked(from_row, from_col) {
+            true => (from_row, from_col),
+            false => to,
+        };
+
+        if remapped.len() <= row {
+            remapped.resize(row + 1, Vec::new());
+        }
+        if remapped[row].len() <= col {
+            remapped[row].resize(col + 1, None);
+        }
+
+        match &mut remapped[row][col] {
+            Some(_) => return Err(invalid()),
+            slot => *slot = Some(item.clone()),
+        }
+    }
+
+    Ok(remapped
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|item| item.unwrap_or_else(|| fill.clone()))
+                .collect()
+        })
+        .collect())
+}
+
+/// Reshape the rows of a layer or fingering between ANSI and ISO, see
+/// [`to_iso()`](crate::DofIntermediate::to_iso). When `swap` is set, the keys on the ANSI backslash and
+/// enter positions swap places, or if the home row ends right before the enter position when converting
+/// to ISO, the backslash key is moved there and `home` is left in its place. Without `swap`, only `home`
+/// is added to the home row. `extra` is inserted at, or removed from, the extra ISO position.
+fn reshape_iso<T>(
+    rows: &mut [Vec<T>],
+    anchor: Anchor,
+    to_iso: bool,
+    swap: bool,
+    home: T,
+    extra: T,
+) {
+    let row = |board_row: usize| board_row.checked_sub(anchor.y());
+    let (backslash, enter, extra_col) = (13 - anchor.x(), 12 - anchor.x(), 1 - anchor.x());
+
+    if let (Some(_), Some(home_row)) = (row(1), row(2).filter(|&r| r < rows.len())) {
+        let (upper, lower) = rows.split_at_mut(home_row);
+        let (top, home_row) = (&mut upper[home_row - 1], &mut lower[0]);
+        let home_len = home_row.len();
+
+        match (top.get_mut(backslash), home_row.get_mut(enter)) {
+            (Some(a), Some(b)) if swap => std::mem::swap(a, b),
+            (Some(a), None) if to_iso && home_len == enter => {
+                let moved = match swap {
+                    true => std::mem::replace(a, home),
+                    false => home,
                 };
+                home_row.push(moved);
+            }
+            _ => {}
+        }
+    }
 
-                fingering.resized(anchor, main.shape()).map(Into::into)
+    if let Some(bottom) = row(3).and_then(|r| rows.get_mut(r)) {
+        match to_iso {
+            true if extra_col <= bottom.len() => bottom.insert(extra_col, extra),
+            false if extra_col < bottom.len() => {
+                bottom.remove(extra_col);
             }
+            _ => {}
         }
     }
 }
@@ -816,14 +2681,27 @@ mod tests {
     fn no_main_layer() {
         let minimal_test = DofIntermediate {
             name: "Qwerty".into(),
+            version: None,
             authors: None,
             board: ParseKeyboard::Named(KeyboardType::Ansi),
             year: None,
             description: None,
             languages: Default::default(),
             link: None,
+            tags: None,
+            corpus: None,
             anchor: None,
-            layers: BTreeMap::new(),
+            base_layer: None,
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: None,
+            case_insensitive_layers: None,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layers: LayerMap::new(),
             fingering: Some(ParsedFingering::Implicit(NamedFingering::Angle)),
         };
 
@@ -838,14 +2716,27 @@ mod tests {
 
         let minimal_test = DofIntermediate {
             name: "Qwerty".into(),
+            version: None,
             authors: None,
             board: ParseKeyboard::Named(KeyboardType::Ansi),
             year: None,
             description: None,
             languages: None,
             link: None,
+            tags: None,
+            corpus: None,
             anchor: None,
-            layers: BTreeMap::new(),
+            base_layer: None,
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: None,
+            case_insensitive_layers: None,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layers: LayerMap::new(),
             fingering: None,
         };
 
@@ -857,7 +2748,7 @@ mod tests {
 
     #[test]
     fn minimal_succesful_dof() {
-        use Finger::*;
+        use Finger::{LI, LM, LP, LR, RI, RM, RP, RR};
         use Key::*;
 
         let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
@@ -866,6 +2757,7 @@ mod tests {
 
         let d_manual = Dof {
             name: "Qwerty".into(),
+            version: None,
             authors: None,
             board: PhysicalKeyboard::try_from(ParseKeyboard::Named(KeyboardType::Ansi))
                 .unwrap()
@@ -877,8 +2769,21 @@ mod tests {
             description: None,
             languages: vec![Default::default()],
             link: None,
+            tags: vec![],
+            corpus: None,
             anchor: Anchor::new(1, 1),
-            layers: BTreeMap::from_iter([
+            base_layer: "main".into(),
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: false,
+            case_insensitive_layers: false,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layer_fingerings: BTreeMap::new(),
+            layers: LayerMap::from_iter([
                 (
                     "main".into(),
                     vec![
@@ -924,7 +2829,7 @@ mod tests {
                 ),
                 (
                     "shift".into(),
-                    Layer(vec![
+                    crate::Layer::from(vec![
                         vec![
                             Char('Q'),
                             Char('W'),
@@ -987,7 +2892,7 @@ mod tests {
 
     #[test]
     fn parse_aptmak() {
-        use Finger::*;
+        use Finger::{LI, LM, LP, LR, LT, RI, RM, RP, RR, RT};
         use Key::*;
 
         let aptmak_json = include_str!("../example_dofs/aptmak.dof");
@@ -996,6 +2901,7 @@ mod tests {
 
         let d_manual = Dof {
             name: "Aptmak".into(),
+            version: None,
             authors: None,
             board: PhysicalKeyboard::try_from(ParseKeyboard::Named(KeyboardType::Colstag))
                 .unwrap()
@@ -1007,8 +2913,21 @@ mod tests {
             description: None,
             languages: vec![Default::default()],
             link: None,
+            tags: vec![],
+            corpus: None,
             anchor: KeyboardType::Colstag.anchor(),
-            layers: BTreeMap::from_iter([
+            base_layer: "main".into(),
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: false,
+            case_insensitive_layers: false,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layer_fingerings: BTreeMap::new(),
+            layers: LayerMap::from_iter([
                 (
                     "main".into(),
                     vec![
@@ -1116,12 +3035,589 @@ mod tests {
             has_generated_shift: true,
         };
 
-        assert_eq!(d, d_manual);
+        assert_eq!(d, d_manual);
+
+        let reconvert_json =
+            serde_json::to_string_pretty(&d).expect("Couldn't reconvert to json value");
+
+        println!("{reconvert_json}")
+    }
+
+    #[test]
+    fn base_layer() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+        assert_eq!(inter.base_layer_name(), Some("main"));
+
+        let main = inter.layers.remove("main").unwrap();
+        inter.layers.insert("qwerty".into(), main.clone());
+        inter.layers.insert("sym".into(), main.clone());
+        assert_eq!(inter.base_layer_name(), Some("qwerty"));
+
+        inter.base_layer = Some("sym".into());
+        assert_eq!(inter.base_layer_name(), Some("sym"));
+
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert to dof");
+        assert_eq!(dof.base_layer_name(), "sym");
+        assert_eq!(dof.main_layer(), &main);
+        assert_eq!(DofIntermediate::from(dof), inter);
+
+        inter.base_layer = Some("missing".into());
+        assert_eq!(inter.main_layer(), Err(DErr::NoMainLayer.into()));
+    }
+
+    #[test]
+    fn no_fingering() {
+        let json = r#"{
+            "name": "In progress",
+            "board": "ansi",
+            "layers": { "main": ["q w e r t  y u i o p"] },
+            "fingering": FINGERING
+        }"#;
+
+        for fingering in ["null", r#""""#] {
+            let inter =
+                serde_json::from_str::<DofIntermediate>(&json.replace("FINGERING", fingering))
+                    .expect("couldn't parse json");
+
+            assert_eq!(inter.fingering, Some(ParsedFingering::None));
+            assert_eq!(
+                inter.explicit_fingering(inter.main_layer().unwrap()),
+                Err(DErr::NoFingering.into())
+            );
+            assert_eq!(
+                inter.sfb_count("main", &analysis::Corpus::from_text("ed")),
+                Err(DErr::NoFingering.into())
+            );
+            assert_eq!(Dof::try_from(inter.clone()), Err(DErr::NoFingering.into()));
+
+            let value = serde_json::to_value(&inter).expect("couldn't serialize");
+            assert_eq!(value["fingering"], serde_json::Value::Null);
+            assert_eq!(
+                serde_json::from_value::<DofIntermediate>(value).expect("couldn't reparse"),
+                inter
+            );
+        }
+
+        let json = json.replace(",\n            \"fingering\": FINGERING", "");
+        let inter = serde_json::from_str::<DofIntermediate>(&json).expect("couldn't parse json");
+
+        assert_eq!(inter.fingering, None);
+    }
+
+    #[test]
+    fn flattened_intermediate() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Wrapper {
+            id: u64,
+            favourite: bool,
+            #[serde(flatten)]
+            layout: DofIntermediate,
+        }
+
+        let extended = include_str!("../example_dofs/minimal_valid.dof").replace(
+            r#""fingering": "angle""#,
+            r#""fingering": null,
+            "anchor": "q",
+            "geometry": { "row_offsets": [0, 0.25], "keys": [["0.5 0.5", "1.5 0.5"]] }"#,
+        );
+        assert!(extended.contains("geometry"));
+
+        for json in [
+            include_str!("../example_dofs/minimal_valid.dof"),
+            include_str!("../example_dofs/maximal.dof"),
+            include_str!("../example_dofs/buggy.dof"),
+            include_str!("../example_dofs/iso.dof"),
+            &extended,
+        ] {
+            let layout =
+                serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+            let mut value = serde_json::from_str::<serde_json::Value>(json).unwrap();
+            value["id"] = 7.into();
+            value["favourite"] = true.into();
+
+            let wrapper =
+                serde_json::from_value::<Wrapper>(value.clone()).expect("couldn't parse wrapper");
+
+            assert_eq!(wrapper.id, 7);
+            assert_eq!(wrapper.layout, layout);
+
+            let serialized = serde_json::to_value(&wrapper).expect("couldn't serialize wrapper");
+            assert_eq!(serialized["id"], 7);
+            assert_eq!(
+                serde_json::from_value::<Wrapper>(serialized).expect("couldn't reparse wrapper"),
+                wrapper
+            );
+        }
+    }
+
+    #[test]
+    fn format_version() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+        assert_eq!(inter.format_version(), None);
+        assert_eq!(inter.validate_version(), Ok(()));
+
+        inter.version = Some(CURRENT_VERSION.into());
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert to dof");
+        assert_eq!(dof.format_version(), Some(CURRENT_VERSION));
+
+        inter.version = Some("0.1".into());
+        assert_eq!(inter.validate_version(), Ok(()));
+
+        inter.version = Some("999.0.0".into());
+        assert_eq!(
+            Dof::try_from(inter.clone()),
+            Err(DErr::NewerVersion("999.0.0".into(), CURRENT_VERSION).into())
+        );
+
+        inter.version = Some("one point oh".into());
+        assert_eq!(
+            inter.validate_version(),
+            Err(DErr::InvalidVersion("one point oh".into()).into())
+        );
+    }
+
+    #[test]
+    fn layer_metadata() {
+        let json = r##"{
+            "name": "Qwerty",
+            "board": "ansi",
+            "layers": {
+                "main": {
+                    "keys": ["q w e r t  y u i o p"],
+                    "color": "#fff",
+                    "description": "the base layer"
+                },
+                "shift": ["Q W E R T  Y U I O P"]
+            },
+            "fingering": "angle"
+        }"##;
+
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+        let main = inter.main_layer().unwrap();
+        assert_eq!(main.shape(), vec![10].into());
+        assert_eq!(main.metadata().color.as_deref(), Some("#fff"));
+        assert_eq!(
+            main.metadata().description.as_deref(),
+            Some("the base layer")
+        );
+        assert!(inter.layers["shift"].metadata().is_empty());
+
+        let value = serde_json::to_value(&inter).expect("couldn't serialize");
+        assert_eq!(value["layers"]["main"]["keys"][0], "q w e r t y u i o p");
+        assert_eq!(value["layers"]["main"]["color"], "#fff");
+        assert_eq!(value["layers"]["shift"][0], "Q W E R T Y U I O P");
+        assert_eq!(
+            serde_json::from_value::<DofIntermediate>(value).expect("couldn't reparse"),
+            inter
+        );
+
+        let bare = Layer::from(main.inner().to_vec());
+
+        assert_ne!(&bare, main);
+        assert_eq!(bare.inner(), main.inner());
+        assert_eq!(bare.with_metadata(main.metadata().clone()), *main);
+    }
+
+    #[test]
+    fn rectangular_ortho() {
+        let json = r#"{
+            "name": "Ortho",
+            "board": "BOARD",
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p",
+                    "a s d f g  h j k l ;",
+                    "z x c v b  n m , .  ",
+                    "      spc  e        "
+                ]
+            },
+            "fingering": "traditional"
+        }"#;
+
+        let inter = serde_json::from_str::<DofIntermediate>(&json.replace("BOARD", "ortho"))
+            .expect("couldn't parse json");
+
+        assert!(!inter.is_rectangular("main"));
+        assert!(!inter.is_rectangular("missing"));
+        assert_eq!(
+            Dof::try_from(inter),
+            Err(DErr::RaggedOrthoLayer(vec![10, 10, 9, 2]).into())
+        );
+
+        let inter = serde_json::from_str::<DofIntermediate>(&json.replace("BOARD", "colstag"))
+            .expect("couldn't parse json");
+
+        assert!(Dof::try_from(inter).is_ok());
+
+        let inter = serde_json::from_str::<DofIntermediate>(
+            &json
+                .replace("BOARD", "ortho")
+                .replace("n m , .  ", "n m , . /"),
+        )
+        .expect("couldn't parse json");
+
+        assert!(inter.is_rectangular("main"));
+        assert!(Dof::try_from(inter).is_ok());
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse json");
+        inter.layers.insert(
+            "square".into(),
+            vec![vec![Key::Char('a'); 3], vec![Key::Char('b'); 3]].into(),
+        );
+
+        assert!(inter.is_rectangular("square"));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn preserve_layer_order() {
+        let json = r#"{
+            "name": "Ordered",
+            "board": "ortho",
+            "layers": {
+                "main": ["a b c"],
+                "shift": ["A B C"],
+                "altgr": ["1 2 3"],
+                "fn": ["~ ~ ~"]
+            },
+            "fingering": ["LI LI LI"]
+        }"#;
+
+        let dof = serde_json::from_str::<Dof>(json).expect("couldn't parse json");
+
+        assert_eq!(
+            dof.layers().keys().collect::<Vec<_>>(),
+            ["main", "shift", "altgr", "fn"]
+        );
+
+        let serialized = serde_json::to_string(&dof).expect("couldn't serialize");
+        let positions = ["\"main\"", "\"shift\"", "\"altgr\"", "\"fn\""]
+            .map(|name| serialized.find(name).expect("layer is missing"));
+
+        assert!(positions.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn shift_consistency() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        let main = inter.main_layer().unwrap().clone();
+        let mut shift = DofIntermediate::generate_shift_layer(&main);
+        shift.0[0][1] = Key::Char('B');
+        shift.0[1][10] = Key::Char('@');
+        shift.0[2][0] = Key::Special(SpecialKey::Shift);
+        inter.layers.insert("shift".into(), shift);
+
+        let us = ('a'..='z')
+            .map(|c| (c, c.to_ascii_uppercase()))
+            .chain([('\'', '"')])
+            .collect::<BTreeMap<_, _>>();
+
+        assert_eq!(
+            inter.check_shift_consistency("main", "shift", &us),
+            Ok(vec![(0, 1, 'w', 'B'), (1, 10, '\'', '@')])
+        );
+
+        let uk = BTreeMap::from_iter([('w', 'B'), ('\'', '@')]);
+        assert_eq!(
+            inter.check_shift_consistency("main", "shift", &uk),
+            Ok(vec![])
+        );
+
+        assert!(inter.check_shift_consistency("main", "altgr", &us).is_err());
+    }
+
+    #[test]
+    fn key_ids() {
+        let json = r#"{
+            "name": "Ids",
+            "board": "ortho",
+            "layers": { "main": ["a b c", "d e f"] },
+            "key_ids": [[0, 1, 2], [10, 11, 12]],
+            "fingering": ["LI LI LI", "LM LM LM"]
+        }"#;
+
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+
+        assert_eq!(inter.key_id_at(0, 0), Some(0));
+        assert_eq!(inter.key_id_at(1, 2), Some(12));
+        assert_eq!(inter.key_id_at(2, 0), None);
+
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert to dof");
+        assert_eq!(
+            dof.key_ids(),
+            Some([vec![0, 1, 2], vec![10, 11, 12]].as_slice())
+        );
+        assert_eq!(DofIntermediate::from(dof), inter);
+
+        let mut inter = inter;
+        inter.key_ids = Some(vec![vec![0, 1]]);
+
+        assert_eq!(
+            Dof::try_from(inter.clone()),
+            Err(DErr::IncompatibleKeyIdsShape.into())
+        );
+
+        inter.key_ids = None;
+        assert_eq!(inter.key_id_at(0, 0), None);
+    }
+
+    #[test]
+    fn reachability_uniqueness() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        assert_eq!(inter.check_reachability_uniqueness(&["main"]), Ok(vec![]));
+
+        let main = inter.main_layer().unwrap().clone();
+        let mut shift = DofIntermediate::generate_shift_layer(&main);
+        shift.0[0][0] = Key::Char('a');
+        inter.layers.insert("shift".into(), shift);
+
+        assert_eq!(
+            inter.check_reachability_uniqueness(&["main", "shift"]),
+            Ok(vec![(
+                'a',
+                vec![("main".to_string(), 1, 0), ("shift".to_string(), 0, 0)]
+            )])
+        );
+        assert!(inter
+            .check_reachability_uniqueness(&["main", "altgr"])
+            .is_err());
+    }
+
+    #[test]
+    fn tags() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        assert_eq!(inter.tags, None);
+        assert!(!serde_json::to_string(&inter).unwrap().contains("tags"));
+
+        inter.tags = Some(vec![]);
+        assert!(!serde_json::to_string(&inter).unwrap().contains("tags"));
+
+        inter.tags = Some(vec![
+            "Symmetric".into(),
+            " 35-key ".into(),
+            "symmetric".into(),
+            "".into(),
+        ]);
+        assert_eq!(inter.normalized_tags(), ["symmetric", "35-key"]);
+
+        let value = serde_json::to_value(&inter).unwrap();
+        assert_eq!(value["tags"][0], "Symmetric");
+
+        let dof = Dof::try_from(inter).expect("couldn't convert to dof");
+        assert_eq!(dof.tags(), ["symmetric", "35-key"]);
+        assert_eq!(
+            DofIntermediate::from(dof).tags,
+            Some(vec!["symmetric".into(), "35-key".into()])
+        );
+    }
+
+    #[test]
+    fn declared_corpus() {
+        let minimal = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(minimal).expect("couldn't parse json");
+
+        assert_eq!(inter.declared_corpus(), None);
+
+        let mut value = serde_json::to_value(&inter).unwrap();
+        value["corpus"] = "english-10k".into();
+        inter = serde_json::from_value(value).expect("couldn't parse json");
+
+        assert_eq!(inter.declared_corpus(), Some("english-10k"));
+
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert");
+        assert_eq!(dof.corpus(), Some("english-10k"));
+        assert_eq!(DofIntermediate::from(dof), inter);
+    }
+
+    #[test]
+    fn fingering_indices() {
+        use Finger::*;
+
+        let fingering = Fingering::from(vec![vec![LP, LR, LT, RT], vec![RI, RM, RR, RP]]);
+        let indices = fingering.to_indices();
+
+        assert_eq!(indices, vec![vec![0, 1, 4, 5], vec![6, 7, 8, 9]]);
+        assert_eq!(Fingering::from_indices(indices), Ok(fingering));
+        assert_eq!(
+            Fingering::from_indices(vec![vec![0, 11]]),
+            Err(DErr::FingerParseError("11".into()).into())
+        );
+        assert_eq!(
+            Fingering::from_indices(vec![vec![0, 10]]),
+            Ok(Fingering::from(vec![vec![LP, Finger::Unassigned]]))
+        );
+        assert_eq!(
+            Fingering::from(vec![vec![Finger::Unassigned]]).to_indices(),
+            vec![vec![10]]
+        );
+    }
+
+    #[test]
+    fn pad_fingering() {
+        use Finger::{LI, LP, RI, RP};
+
+        let json = r#"{
+            "name": "Partial",
+            "board": "ansi",
+            "layers": { "main": ["q w e r t  y u i o p  [", "a s d f g  h j k l ;"] },
+            "fingering": ["LP - LM LI LI  RI RI RM RR RP", "0 1 2 3 3  6 6 7 8 x"]
+        }"#;
+
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+        let Some(ParsedFingering::Explicit(mut fingering)) = inter.fingering.clone() else {
+            panic!("fingering should be explicit")
+        };
+
+        assert_eq!(fingering.inner()[0][1], Finger::Unassigned);
+        assert_eq!(fingering.inner()[1][9], Finger::Unassigned);
+
+        fingering.pad_to_shape(&[11, 10, 2], Finger::Unassigned);
+
+        assert_eq!(fingering.shape(), Shape::from([11, 10, 2]));
+        assert_eq!(fingering.inner()[0][..1], [LP]);
+        assert_eq!(fingering.inner()[0][10], Finger::Unassigned);
+        assert_eq!(
+            fingering.inner()[2],
+            [Finger::Unassigned, Finger::Unassigned]
+        );
+
+        fingering.pad_to_shape(&[5], LI);
+        assert_eq!(fingering.shape(), Shape::from([11, 10, 2]));
+
+        let mut short = Fingering::from(vec![vec![RI], vec![]]);
+        short.pad_to_shape(&[2, 1], RP);
+        assert_eq!(short, Fingering::from(vec![vec![RI, RP], vec![RP]]));
+
+        let mut inter = inter;
+        fingering.pad_to_shape(&[11, 10], Finger::Unassigned);
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(
+            fingering.inner()[..2].to_vec(),
+        )));
+
+        assert_eq!(inter.finger_for_char("main", 'w'), Ok(None));
+        assert_eq!(inter.finger_for_char("main", 'q'), Ok(Some(LP)));
+        assert_eq!(
+            inter.unfingered_keys("main"),
+            Ok(vec![(0, 1).into(), (0, 10).into(), (1, 9).into()])
+        );
+
+        let serialized = serde_json::to_value(&inter).expect("couldn't serialize");
+        assert_eq!(serialized["fingering"][0], "LP - LM LI LI RI RI RM RR RP -");
+    }
 
-        let reconvert_json =
-            serde_json::to_string_pretty(&d).expect("Couldn't reconvert to json value");
+    #[test]
+    fn named_anchor() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
 
-        println!("{reconvert_json}")
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        inter.anchor = serde_json::from_str(r#""f""#).expect("couldn't parse anchor");
+        assert_eq!(inter.anchor, Some(ParsedAnchor::Char('f')));
+        assert_eq!(inter.resolved_anchor(), Ok(Anchor::new(3, 1)));
+
+        inter.anchor = serde_json::from_str("[0, 0]").expect("couldn't parse anchor");
+        assert_eq!(inter.anchor, Some(Anchor::new(0, 0).into()));
+        assert_eq!(inter.resolved_anchor(), Ok(Anchor::new(0, 0)));
+
+        inter.anchor = Some(ParsedAnchor::Char('q'));
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert to dof");
+        assert_eq!(dof.anchor(), Anchor::new(0, 0));
+
+        let json = serde_json::to_value(&dof).expect("couldn't serialize dof");
+        assert_eq!(json["anchor"], serde_json::json!([0, 0]));
+
+        inter.anchor = Some(ParsedAnchor::Char('é'));
+        assert_eq!(
+            inter.resolved_anchor(),
+            Err(DErr::AnchorCharNotFound('é').into())
+        );
+    }
+
+    #[test]
+    fn validate_anchor() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        let mut fingering = inter
+            .explicit_fingering(inter.main_layer().unwrap())
+            .unwrap();
+        inter.fingering = Some(ParsedFingering::Explicit(fingering.clone()));
+
+        assert_eq!(inter.validate_anchor(false), Ok(()));
+        assert_eq!(
+            inter.validate_anchor(true),
+            Err(DErr::AnchorNotOnIndex(Anchor::new(1, 1), Finger::LR).into())
+        );
+
+        inter.anchor = Some(ParsedAnchor::Char('f'));
+        assert_eq!(inter.validate_anchor(true), Ok(()));
+
+        inter.anchor = Some(Anchor::new(11, 0).into());
+        assert_eq!(
+            inter.validate_anchor(false),
+            Err(DErr::AnchorBiggerThanLayout.into())
+        );
+
+        inter.anchor = Some(ParsedAnchor::Char('f'));
+        fingering.0[1][3] = Finger::Unassigned;
+        inter.fingering = Some(ParsedFingering::Explicit(fingering));
+        assert_eq!(
+            inter.validate_anchor(false),
+            Err(DErr::AnchorNotFingered(Anchor::new(3, 1)).into())
+        );
+    }
+
+    #[test]
+    fn parse_iso() {
+        use Finger::*;
+
+        let iso_json = include_str!("../example_dofs/iso.dof");
+
+        let d = serde_json::from_str::<Dof>(iso_json).expect("Couldn't parse iso dof");
+
+        assert_eq!(d.shape(), vec![13, 12, 11].into());
+        assert_eq!(d.main_layer().0[0][12], Key::Special(SpecialKey::IsoEnter));
+        assert_eq!(d.main_layer().0[2][0], Key::Char('\\'));
+        assert_eq!(
+            d.fingering().0[2],
+            vec![LP, LR, LM, LI, LI, LI, RI, RI, RM, RR, RP]
+        );
+        assert_eq!(
+            d.board().inner()[0][12],
+            PhysicalKey::xywh(13.5, 1.0, 1.5, 2.0)
+        );
+
+        let json = serde_json::to_string(&d).expect("couldn't serialize iso dof");
+
+        assert!(json.contains("iso-enter"));
+        assert_eq!(
+            serde_json::from_str::<Dof>(&json).expect("couldn't reparse iso dof"),
+            d
+        );
     }
 
     #[test]
@@ -1135,14 +3631,27 @@ mod tests {
     fn deserialize_minimal() {
         let minimal_test = DofIntermediate {
             name: "Qwerty".into(),
+            version: None,
             authors: None,
             board: ParseKeyboard::Named(KeyboardType::Ansi),
             year: None,
             description: None,
             languages: None,
             link: None,
+            tags: None,
+            corpus: None,
             anchor: None,
-            layers: BTreeMap::new(),
+            base_layer: None,
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: None,
+            case_insensitive_layers: None,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layers: LayerMap::new(),
             fingering: Some(ParsedFingering::Implicit(NamedFingering::Angle)),
         };
 
@@ -1170,7 +3679,7 @@ mod tests {
 
     #[test]
     fn parse_maximal() {
-        use Finger::*;
+        use Finger::{LI, LM, LP, LR, LT, RI, RM, RP, RR, RT};
         use Key::*;
         use SpecialKey::*;
 
@@ -1178,13 +3687,26 @@ mod tests {
 
         let maximal_test = DofIntermediate {
             name: "Qwerty".into(),
+            version: None,
             authors: Some(vec!["Christopher Latham Sholes".into()]),
             year: Some(1878),
             description: Some("the OG. Without Qwerty, none of this would be necessary.".into()),
             languages: None,
             link: Some("https://en.wikipedia.org/wiki/QWERTY".into()),
-            anchor: Some(Anchor::new(0, 0)),
-            layers: BTreeMap::from_iter([
+            tags: None,
+            corpus: None,
+            anchor: Some(Anchor::new(0, 0).into()),
+            base_layer: None,
+            geometry: None,
+            key_ids: None,
+            thumbs: None,
+            thumb_fingering: None,
+            auto_shift: None,
+            case_insensitive_layers: None,
+            fingerings: None,
+            locked: None,
+            checksum: None,
+            layers: LayerMap::from_iter([
                 (
                     "main".into(),
                     crate::Layer::from(vec![
@@ -1417,13 +3939,17 @@ mod tests {
                 ),
             ]),
             fingering: {
-                Some(ParsedFingering::Explicit(Fingering::from(vec![
+                let mut fingering = Fingering::from(vec![
                     vec![LP, LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP, RP],
                     vec![LP, LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP, RP],
                     vec![LP, LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP, RP],
                     vec![LP, LR, LM, LI, LI, LI, RI, RI, RM, RR, RP, RP],
                     vec![LP, LP, LT, LT, LT, RT, RT, RP],
-                ])))
+                ]);
+                // The first row is written with numbers.
+                fingering.1[0] = RowNotation::Numeric;
+
+                Some(ParsedFingering::Explicit(fingering))
             },
             board: ParseKeyboard::Relative(RelativeKeyboard::from(vec![
                 vec![
@@ -1506,6 +4032,1010 @@ mod tests {
         assert_eq!(dof_maximal, maximal_test);
     }
 
+    #[test]
+    fn explicit_geometry() {
+        let json = r#"{
+            "name": "Split",
+            "board": "ortho",
+            "layers": {
+                "main": [
+                    "a b",
+                    "c d"
+                ]
+            },
+            "fingering": [
+                "LI RI",
+                "LI RI"
+            ],
+            "geometry": {
+                "row_offsets": [0, 0.5],
+                "keys": [
+                    ["0 0", "5 0"],
+                    ["0 1", "5 1"]
+                ]
+            }
+        }"#;
+
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+        let geometry = inter
+            .resolved_geometry()
+            .expect("couldn't resolve geometry");
+
+        assert_eq!(geometry.position((1, 1)), Some((5.5, 1.0)));
+
+        let dof = Dof::try_from(inter.clone()).expect("couldn't convert to Dof");
+
+        assert_eq!(dof.geometry(), geometry);
+        assert_eq!(DofIntermediate::from(dof), inter);
+
+        let minimal = include_str!("../example_dofs/minimal_valid.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(minimal).expect("couldn't parse json");
+        let geometry = inter
+            .resolved_geometry()
+            .expect("couldn't resolve geometry");
+        let dof = Dof::try_from(inter).expect("couldn't convert to Dof");
+
+        assert_eq!(geometry.position((0, 0)), Some((2.0, 1.5)));
+        assert_eq!(dof.geometry(), geometry);
+    }
+
+    #[test]
+    fn check_shift_layer() {
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.check_shift_layer("main", "shift"),
+            Err(DErr::ShiftedModifiers(vec![(4, 5)]).into())
+        );
+        assert_eq!(inter.check_shift_layer("main", "altgr"), Ok(()));
+
+        inter.layers.get_mut("shift").unwrap().0[4][5] = Key::Transparent;
+
+        assert_eq!(inter.check_shift_layer("main", "shift"), Ok(()));
+        assert!(inter.check_shift_layer("main", "l2").is_err());
+    }
+
+    #[test]
+    fn validate_layer_skeleton() {
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(
+            inter.validate_layer_skeleton("main"),
+            Err(DErr::SkeletonOverridden(vec![("shift".into(), 4, 5)]).into())
+        );
+
+        inter.layers.get_mut("shift").unwrap().0[4][5] = Key::Transparent;
+        assert_eq!(inter.validate_layer_skeleton("main"), Ok(()));
+
+        inter.layers.get_mut("altgr").unwrap().0[2][0] = Key::Char('x');
+        assert_eq!(
+            inter.validate_layer_skeleton("main"),
+            Err(DErr::SkeletonOverridden(vec![("altgr".into(), 2, 0)]).into())
+        );
+
+        assert!(inter.validate_layer_skeleton("nope").is_err());
+    }
+
+    #[test]
+    fn validate_printable() {
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        assert_eq!(inter.validate_printable(), Ok(()));
+
+        inter.layers.get_mut("main").unwrap().0[1][2] = Key::Char('\u{7}');
+        inter.layers.get_mut("shift").unwrap().0[0][1] = Key::Char('\u{a0}');
+
+        assert_eq!(
+            inter.validate_printable(),
+            Err(DErr::UnprintableChars(vec![
+                ("main".into(), 1, 2, 7),
+                ("shift".into(), 0, 1, 0xa0)
+            ])
+            .into())
+        );
+    }
+
+    #[test]
+    fn one_finger_per_key() {
+        use Finger::*;
+
+        let minimal = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(minimal).expect("couldn't parse json");
+
+        assert_eq!(inter.assert_one_finger_per_key("main"), Ok(()));
+
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+        ])));
+
+        assert_eq!(
+            inter.assert_one_finger_per_key("main"),
+            Err(DErr::FingerCountMismatch(1, 10).into())
+        );
+
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP],
+            vec![LP, LR, LM, LI, LI, RI, RI, RM, RR, RP, RP],
+        ])));
+
+        assert_eq!(
+            inter.assert_one_finger_per_key("main"),
+            Err(DErr::FingerCountMismatch(2, 0).into())
+        );
+        assert!(inter.assert_one_finger_per_key("altgr").is_err());
+    }
+
+    #[test]
+    fn case_insensitive_layers() {
+        let buggy_json = include_str!("../example_dofs/buggy.dof");
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(buggy_json).expect("couldn't parse json");
+
+        let l2 = inter.layers.remove("l2").unwrap();
+        inter.layers.insert("L2".into(), l2.clone());
+
+        assert_eq!(inter.layer_ci("l2"), Some(&l2));
+        assert_eq!(inter.layer_ci("L2"), Some(&l2));
+        assert_eq!(inter.layer_ci("l3"), None);
+
+        let main = inter.main_layer().unwrap();
+
+        assert_eq!(
+            inter.validate_layer_keys(main),
+            Err(DErr::LayersNotFound(vec!["l2".into()]).into())
+        );
+        assert_eq!(inter.validate_layer_keys_ci(main), Ok(()));
+
+        assert_eq!(
+            Dof::try_from(inter.clone()),
+            Err(DErr::LayersNotFound(vec!["l2".into()]).into())
+        );
+
+        inter.case_insensitive_layers = Some(true);
+        let dof = Dof::try_from(inter).expect("couldn't parse case-insensitive layers");
+
+        assert!(dof.case_insensitive_layers());
+        assert_eq!(
+            DofIntermediate::from(dof).case_insensitive_layers,
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn validate_layer_names() {
+        let buggy_json = include_str!("../example_dofs/buggy.dof");
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(buggy_json).expect("couldn't parse json");
+
+        assert_eq!(inter.validate_layer_names(), Ok(()));
+
+        let l2 = inter.layers.remove("l2").unwrap();
+        inter.layers.insert("L2".into(), l2.clone());
+        inter.layers.insert("l2".into(), l2.clone());
+
+        assert_eq!(
+            inter.validate_layer_names(),
+            Err(DErr::LayerNamesDifferInCase(vec!["L2".into(), "l2".into()]).into())
+        );
+
+        inter.layers.insert("".into(), l2);
+        assert_eq!(
+            inter.validate_layer_names(),
+            Err(DErr::EmptyLayerName.into())
+        );
+    }
+
+    #[test]
+    fn json_error_conversion() {
+        fn parse(s: &str) -> Result<Dof> {
+            Ok(serde_json::from_str::<Dof>(s)?)
+        }
+
+        let err = parse("{").expect_err("parsing should fail");
+        assert!(matches!(err.0.as_ref(), DErr::Json(_)));
+
+        let err = serde_json::Error::from(DofError::from(DErr::NoMainLayer));
+        assert_eq!(err.to_string(), "This layout is missing a main layer");
+    }
+
+    #[test]
+    fn json_value_conversion() {
+        let minimal_json = include_str!("../example_dofs/minimal_valid.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(minimal_json)
+            .expect("Couldn't parse minimal dof");
+
+        let value = serde_json::Value::try_from(&inter).expect("couldn't convert to value");
+
+        assert_eq!(value["name"], "Qwerty");
+        assert_eq!(value["layers"]["main"][1], "a s d f g h j k l ; '");
+        assert_eq!(DofIntermediate::try_from(value), Ok(inter));
+
+        let err = DofIntermediate::try_from(serde_json::json!({ "name": "Nothing" }))
+            .expect_err("converting should fail");
+        assert!(matches!(err.0.as_ref(), DErr::Json(_)));
+    }
+
+    #[test]
+    fn fingering_numeric_string() {
+        let minimal = include_str!("../example_dofs/minimal_valid.dof");
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(minimal).expect("couldn't parse json");
+
+        let rows = inter.fingering_numeric_string("main").unwrap();
+        assert_eq!(rows[1], "0 1 2 3 3 6 6 7 8 9 9");
+
+        let numeric = serde_json::from_value::<Fingering>(serde_json::json!(rows))
+            .expect("couldn't parse numeric fingering");
+        assert_eq!(
+            numeric.inner(),
+            inter
+                .explicit_fingering(inter.main_layer().unwrap())
+                .unwrap()
+                .inner()
+        );
+        assert_eq!(numeric.notation(), Ok(FingerNotation::Numeric));
+        assert_eq!(Vec::<String>::from(numeric), rows);
+
+        inter.fingering = Some(ParsedFingering::None);
+        assert!(inter.fingering_numeric_string("main").is_err());
+    }
+
+    #[test]
+    fn fingering_notation() {
+        let parse =
+            |json: &str| serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse");
+
+        let maximal = parse(include_str!("../example_dofs/maximal.dof"));
+        let buggy = parse(include_str!("../example_dofs/buggy.dof"));
+        let minimal = parse(include_str!("../example_dofs/minimal_valid.dof"));
+
+        let err = maximal
+            .fingering_notation()
+            .expect_err("maximal mixes notations");
+        assert!(matches!(err.0.as_ref(), DErr::MixedFingerNotation));
+        assert_eq!(buggy.fingering_notation(), Ok(FingerNotation::Numeric));
+        assert_eq!(minimal.fingering_notation(), Ok(FingerNotation::Named));
+
+        let letters = serde_json::from_str::<Fingering>(r#"["LP LR -", "x RI RP"]"#)
+            .expect("couldn't parse fingering");
+        assert_eq!(letters.notation(), Ok(FingerNotation::Letters));
+
+        let fingering = Fingering::from(vec![vec![Finger::LP, Finger::Unassigned]]);
+        assert_eq!(fingering.notation(), Ok(FingerNotation::Letters));
+
+        let numeric = serde_json::from_str::<Fingering>(r#"["0 1 -", "x 6 9"]"#)
+            .expect("couldn't parse fingering");
+        assert_eq!(numeric.inner(), letters.inner());
+        assert_ne!(numeric, letters);
+        assert_eq!(
+            serde_json::to_string(&numeric).expect("couldn't serialize fingering"),
+            r#"["0 1 -","- 6 9"]"#
+        );
+
+        let mut layout = buggy.clone();
+        layout.thumb_fingering = Some(letters.clone());
+        assert!(layout.fingering_notation().is_err());
+
+        let mut layout = minimal.clone();
+        layout.fingerings = Some(BTreeMap::from_iter([(
+            "shift".into(),
+            ParsedFingering::Explicit(numeric),
+        )]));
+        assert_eq!(layout.fingering_notation(), Ok(FingerNotation::Numeric));
+
+        layout.thumb_fingering = Some(letters);
+        let err = layout
+            .fingering_notation()
+            .expect_err("thumbs and shift use different notations");
+        assert!(matches!(err.0.as_ref(), DErr::MixedFingerNotation));
+    }
+
+    #[test]
+    fn minimal_json() {
+        let mut inter = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse minimal dof");
+
+        let main = inter.main_layer().unwrap().clone();
+        let fingering = inter.explicit_fingering(&main).unwrap();
+
+        inter.fingering = Some(ParsedFingering::Explicit(fingering.clone()));
+        inter.anchor = Some(ParsedAnchor::Position(Anchor::new(1, 1)));
+        inter.base_layer = Some("main".into());
+        inter.languages = Some(vec![Language::default()]);
+
+        let minimal = inter.to_minimal_json().expect("couldn't serialize");
+        let value = serde_json::from_str::<serde_json::Value>(&minimal).unwrap();
+
+        assert_eq!(value["fingering"], "angle");
+        assert!(value.get("anchor").is_none());
+        assert!(value.get("base_layer").is_none());
+        assert!(value.get("languages").is_none());
+
+        let mut custom = fingering.into_inner();
+        custom[0].swap(0, 1);
+        inter.fingering = Some(ParsedFingering::Explicit(custom.into()));
+
+        let minimal = inter.to_minimal_json().expect("couldn't serialize");
+        let value = serde_json::from_str::<serde_json::Value>(&minimal).unwrap();
+
+        assert_eq!(value["fingering"][0], "LR LP LM LI LI RI RI RM RR RP");
+    }
+
+    #[test]
+    fn identify_fingering() {
+        let shape = Shape::from(vec![10, 10, 10]);
+
+        for named in [
+            NamedFingering::Traditional,
+            NamedFingering::Angle,
+            NamedFingering::Wide,
+            NamedFingering::AngleWide,
+        ] {
+            let fingering: Fingering = KeyboardType::Ansi
+                .fingering(&named)
+                .and_then(|f| f.resized(KeyboardType::Ansi.anchor(), shape.clone()))
+                .unwrap()
+                .into();
+
+            assert_eq!(fingering.identify(KeyboardType::Ansi), Some(named));
+        }
+
+        let ortho: Fingering = KeyboardType::Ortho
+            .fingering(&NamedFingering::Traditional)
+            .and_then(|f| f.resized(KeyboardType::Ortho.anchor(), shape.clone()))
+            .unwrap()
+            .into();
+
+        assert_eq!(
+            ortho.identify(KeyboardType::Ortho),
+            Some(NamedFingering::Traditional)
+        );
+
+        let mut custom = ortho.into_inner();
+        custom[1][0] = Finger::LR;
+
+        assert_eq!(Fingering::from(custom).identify(KeyboardType::Ortho), None);
+    }
+
+    #[test]
+    fn as_rows() {
+        let layer = Layer::from(vec![
+            vec![Key::Char('a')],
+            vec![Key::Empty, Key::Transparent],
+        ]);
+        let fingering =
+            Fingering::from(vec![vec![Finger::LP], vec![Finger::LR, Finger::Unassigned]]);
+
+        assert_eq!(layer.as_rows(), layer.inner());
+        assert_eq!(layer.as_rows()[1], vec![Key::Empty, Key::Transparent]);
+        assert_eq!(fingering.as_rows(), fingering.inner());
+        assert_eq!(fingering.as_rows()[1], vec![Finger::LR, Finger::Unassigned]);
+    }
+
+    #[test]
+    fn push_row() {
+        let mut layer = Layer::new();
+        layer
+            .push_row_str("q w \"Caps Lock\" spc")
+            .expect("couldn't parse row");
+        layer.push_row(vec![Key::Char('a')]);
+
+        assert_eq!(
+            layer.inner(),
+            &[
+                vec![
+                    Key::Char('q'),
+                    Key::Char('w'),
+                    Key::Word("Caps Lock".into()),
+                    Key::Special(SpecialKey::Space),
+                ],
+                vec![Key::Char('a')],
+            ]
+        );
+        assert_eq!(Layer::new(), Layer::default());
+    }
+
+    #[test]
+    fn column_counts() {
+        let layer = Layer::from(vec![
+            vec![Key::Char('a'), Key::Transparent, Key::Char('b')],
+            vec![Key::Transparent, Key::Empty],
+        ]);
+
+        assert_eq!(
+            layer.column_counts(),
+            BTreeMap::from([(0, 2), (1, 2), (2, 1)])
+        );
+        assert_eq!(
+            layer.non_transparent_column_counts(),
+            BTreeMap::from([(0, 1), (1, 1), (2, 1)])
+        );
+    }
+
+    #[test]
+    fn display_shape() {
+        let layer = Layer::from(vec![
+            vec![Key::Char('a'), Key::Char('あ'), Key::Empty],
+            vec![Key::Word("日本".into()), Key::Special(SpecialKey::Space)],
+        ]);
+
+        assert_eq!(layer.display_shape(), vec![vec![1, 2, 1], vec![4, 3]]);
+        assert_eq!(layer.shape(), Shape::from(vec![3, 2]));
+    }
+
+    #[test]
+    fn remap_order() {
+        let ansi = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse minimal dof");
+
+        let mapping = ansi
+            .main_layer()
+            .unwrap()
+            .rows()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| (y, x)))
+            .map(|pos| if pos == (1, 10) { (3, 0) } else { pos })
+            .collect::<Vec<_>>();
+
+        let ortho = ansi
+            .remap_order(KeyboardType::Ansi, KeyboardType::Ortho, &mapping)
+            .expect("couldn't remap");
+        let main = &ortho.layers["main"];
+        let fingering = ortho.explicit_fingering(main).unwrap();
+        let old_fingering = ansi.explicit_fingering(ansi.main_layer().unwrap()).unwrap();
+
+        assert_eq!(ortho.board, ParseKeyboard::Named(KeyboardType::Ortho));
+        assert_eq!(main.shape(), Shape::from([10, 10, 10, 1]));
+        assert_eq!(main.0[3][0], Key::Char('\''));
+        assert_eq!(fingering.0[3][0], old_fingering.0[1][10]);
+        assert_eq!(fingering.0[1][3], old_fingering.0[1][3]);
+
+        assert_eq!(
+            ansi.remap_order(KeyboardType::Iso, KeyboardType::Ortho, &mapping),
+            Err(DErr::UnexpectedBoard(KeyboardType::Iso).into())
+        );
+        assert_eq!(
+            ansi.remap_order(KeyboardType::Ansi, KeyboardType::Ortho, &mapping[1..]),
+            Err(DErr::InvalidRemapping(30).into())
+        );
+
+        let mut duplicate = mapping.clone();
+        duplicate[1] = (0, 0);
+        assert_eq!(
+            ansi.remap_order(KeyboardType::Ansi, KeyboardType::Ortho, &duplicate),
+            Err(DErr::InvalidRemapping(31).into())
+        );
+
+        let mut locked = ansi.clone();
+        locked.locked = Some(vec![Anchor::new(10, 1)]);
+
+        assert!(locked.is_locked(1, 10));
+        assert!(!locked.is_locked(10, 1));
+
+        let ortho = locked
+            .remap_order(KeyboardType::Ansi, KeyboardType::Ortho, &mapping)
+            .expect("couldn't remap");
+        let main = &ortho.layers["main"];
+
+        assert_eq!(main.shape(), Shape::from([10, 11, 10]));
+        assert_eq!(main.0[1][10], Key::Char('\''));
+
+        duplicate[1] = (1, 10);
+        duplicate[0] = (0, 0);
+        assert_eq!(
+            locked.remap_order(KeyboardType::Ansi, KeyboardType::Ortho, &duplicate),
+            Err(DErr::InvalidRemapping(31).into())
+        );
+    }
+
+    #[test]
+    fn mirror() {
+        let json = r#"{
+            "name": "Mirror",
+            "board": "ortho",
+            "layers": {
+                "main": ["a b c d e", "f g h i j"],
+                "shift": ["A B C D E", "F G H I J"]
+            },
+            "fingering": ["LP LR LI RI RP", "LP LR LI RI RP"],
+            "locked": [[1, 1]]
+        }"#;
+
+        let inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse json");
+        let mirrored = inter.mirror();
+
+        assert_eq!(
+            mirrored.layers["main"],
+            Layer::from(vec![
+                "e d c b a".split(' ').map(Key::from).collect(),
+                "j g h i f".split(' ').map(Key::from).collect(),
+            ])
+        );
+        assert_eq!(mirrored.layers["shift"].0[1][1], Key::Char('G'));
+        assert_eq!(mirrored.fingering, inter.fingering);
+        assert_eq!(mirrored.mirror(), inter);
+        assert!(Dof::try_from(mirrored).is_ok());
+
+        let mut outside = inter;
+        outside.locked = Some(vec![
+            Anchor::new(1, 1),
+            Anchor::new(5, 0),
+            Anchor::new(0, 2),
+        ]);
+
+        assert_eq!(
+            Dof::try_from(outside),
+            Err(
+                DErr::LockedPositionsNotOnLayout(vec![Anchor::new(5, 0), Anchor::new(0, 2)]).into()
+            )
+        );
+    }
+
+    #[test]
+    fn ansi_iso_conversion() {
+        let ansi = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse minimal dof");
+
+        let iso = ansi.to_iso().expect("couldn't convert to iso");
+        let main = &iso.layers["main"];
+
+        assert_eq!(iso.board, ParseKeyboard::Named(KeyboardType::Iso));
+        assert_eq!(main.shape(), Shape::from(vec![10, 11, 11]));
+        assert_eq!(main.0[2][0], Key::Empty);
+        assert_eq!(main.0[2][1], Key::Char('z'));
+        assert!(Dof::try_from(iso.clone()).is_ok());
+        assert_eq!(iso.to_ansi(), Ok(ansi.clone()));
+
+        let mut wide = ansi.clone();
+        let main = wide.layers.get_mut("main").unwrap();
+        main.0[0].extend([Key::Char('['), Key::Char(']'), Key::Char('\\')]);
+        let fingering = wide.explicit_fingering(&wide.layers["main"]).unwrap();
+        wide.fingering = Some(ParsedFingering::Explicit(fingering));
+
+        wide.layers.insert("fn".into(), wide.layers["main"].clone());
+        let fn_fingering = wide.explicit_fingering_for("fn").unwrap();
+        wide.fingerings = Some(BTreeMap::from([(
+            "fn".to_string(),
+            ParsedFingering::Explicit(fn_fingering),
+        )]));
+        assert!(Dof::try_from(wide.clone()).is_ok());
+
+        let iso = wide.to_iso().expect("couldn't convert to iso");
+        let main = &iso.layers["main"];
+
+        assert_eq!(
+            iso.explicit_fingering_for("fn"),
+            iso.explicit_fingering(main)
+        );
+        assert_eq!(main.0[0][12], Key::Special(SpecialKey::IsoEnter));
+        assert_eq!(main.0[1][11], Key::Char('\\'));
+        assert!(Dof::try_from(iso.clone()).is_ok());
+
+        let back = iso.to_ansi().expect("couldn't convert back to ansi");
+        assert_eq!(
+            back.explicit_fingering_for("fn"),
+            back.explicit_fingering(&back.layers["main"])
+        );
+        assert_eq!(back.layers["main"].0[0][12], Key::Char('\\'));
+        assert_eq!(
+            back.layers["main"].0[1][11],
+            Key::Special(SpecialKey::Enter)
+        );
+
+        let err = iso.to_iso().expect_err("iso can't be converted to iso");
+        assert!(matches!(
+            err.0.as_ref(),
+            DErr::UnsupportedIsoConversion(KeyboardType::Ansi)
+        ));
+    }
+
+    #[test]
+    fn fork() {
+        let maximal = include_str!("../example_dofs/maximal.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(maximal).expect("couldn't parse json");
+
+        let fork = inter.fork("Qwerty Fork", vec!["me".into()]);
+
+        assert_eq!(fork.name, "Qwerty Fork");
+        assert_eq!(fork.authors, Some(vec!["me".into()]));
+        assert_eq!(fork.year, None);
+        assert_eq!(fork.link, None);
+        assert_eq!(fork.description.as_deref(), Some("Forked from Qwerty"));
+        assert_eq!(fork.layers, inter.layers);
+        assert_eq!(fork.fingering, inter.fingering);
+        assert!(Dof::try_from(fork).is_ok());
+    }
+
+    #[test]
+    fn fingering_diff() {
+        let shape = Shape::from(vec![10, 11, 10]);
+        let fingering = |named| -> Fingering {
+            KeyboardType::Ansi
+                .fingering(&named)
+                .and_then(|f| f.resized(KeyboardType::Ansi.anchor(), shape.clone()))
+                .unwrap()
+                .into()
+        };
+
+        let traditional = fingering(NamedFingering::Traditional);
+        let angle = fingering(NamedFingering::Angle);
+
+        assert_eq!(traditional.diff(&traditional), Ok(vec![]));
+        assert_eq!(
+            traditional.diff(&angle),
+            Ok(vec![
+                ((2, 0), Finger::LP, Finger::LR),
+                ((2, 1), Finger::LR, Finger::LM),
+                ((2, 2), Finger::LM, Finger::LI),
+            ])
+        );
+
+        let mut shorter = angle.into_inner();
+        shorter[0].pop();
+
+        let err = traditional
+            .diff(&shorter.into())
+            .expect_err("shapes don't match");
+        assert!(matches!(err.0.as_ref(), DErr::IncompatibleFingeringShape));
+    }
+
+    #[test]
+    fn by_hand() {
+        use Finger::*;
+
+        let fingering =
+            Fingering::from(vec![vec![LP, LI, RI, RP], vec![LT, Finger::Unassigned, RT]]);
+
+        assert_eq!(
+            fingering.by_hand(None),
+            (vec![vec![LP, LI], vec![LT]], vec![vec![RI, RP], vec![RT]])
+        );
+        assert_eq!(
+            fingering.by_hand(Some(Hand::Right)),
+            (vec![vec![LP, LI], vec![]], vec![vec![RI, RP], vec![LT, RT]])
+        );
+
+        let mut layer = Layer::new();
+        layer.push_row_str("a b c d").expect("couldn't parse row");
+        layer.push_row_str("spc ~ ent").expect("couldn't parse row");
+
+        let (left, right) = layer.by_hand(&fingering, Some(Hand::Left));
+
+        assert_eq!(left[0], [Key::Char('a'), Key::Char('b')]);
+        assert_eq!(
+            left[1],
+            [
+                Key::Special(SpecialKey::Space),
+                Key::Special(SpecialKey::Enter)
+            ]
+        );
+        assert_eq!(right, [vec![Key::Char('c'), Key::Char('d')], vec![]]);
+    }
+
+    #[test]
+    fn thumb_cluster() {
+        let json = r#"{
+            "name": "Thumbs",
+            "board": "colstag",
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p",
+                    "a s d f g  h j k l ;",
+                    "z x c v b  n m , . /"
+                ]
+            },
+            "thumbs": ["tab spc  ret bsp bsp"],
+            "thumb_fingering": ["LT LT  RT RT RT"],
+            "fingering": "traditional"
+        }"#;
+
+        let dof = serde_json::from_str::<Dof>(json).expect("couldn't parse thumbs");
+
+        assert_eq!(dof.thumbs().map(|t| t.shape()), Some(Shape::from(vec![5])));
+        assert_eq!(
+            dof.thumb_fingering(),
+            Some(&Fingering::from(vec![vec![
+                Finger::LT,
+                Finger::LT,
+                Finger::RT,
+                Finger::RT,
+                Finger::RT
+            ]]))
+        );
+
+        let mut inter = DofIntermediate::from(dof);
+        assert!(inter.validate_thumbs().is_ok());
+
+        inter.thumbs = None;
+        let err = Dof::try_from(inter).expect_err("thumb fingering without thumbs");
+        assert!(matches!(
+            err.0.as_ref(),
+            DErr::IncompatibleThumbFingeringShape
+        ));
+    }
+
+    #[test]
+    fn pad_layers() {
+        let json = r#"{
+            "name": "Ragged",
+            "board": "ortho",
+            "layers": {
+                "main": [
+                    "q w e",
+                    "a s"
+                ],
+                "altgr": [
+                    "1 2",
+                    "4 5 6",
+                    "7"
+                ]
+            },
+            "fingering": [
+                "LP LR LM",
+                "LP LR"
+            ],
+            "fingerings": {
+                "altgr": [
+                    "LP LR",
+                    "LP LR LM",
+                    "LP"
+                ]
+            }
+        }"#;
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse");
+        inter.key_ids = Some(vec![vec![0, 1, 2], vec![10, 11]]);
+
+        assert_eq!(inter.pad_layers(Key::Transparent), vec![3, 3, 1]);
+        assert_eq!(inter.key_ids, None);
+        assert_eq!(
+            inter.fingering_for("altgr"),
+            Ok(&ParsedFingering::Explicit(
+                vec![
+                    vec![Finger::LP, Finger::LR, Finger::Unassigned],
+                    vec![Finger::LP, Finger::LR, Finger::LM],
+                    vec![Finger::LP],
+                ]
+                .into()
+            ))
+        );
+        assert_eq!(
+            inter.layers["main"].0[1],
+            vec![Key::Char('a'), Key::Char('s'), Key::Transparent]
+        );
+        assert_eq!(inter.layers["main"].0[2], vec![Key::Transparent]);
+        assert_eq!(
+            inter.layers["altgr"].0[0],
+            vec![Key::Char('1'), Key::Char('2'), Key::Transparent]
+        );
+        assert_eq!(
+            inter.fingering,
+            Some(ParsedFingering::Explicit(
+                vec![
+                    vec![Finger::LP, Finger::LR, Finger::LM],
+                    vec![Finger::LP, Finger::LR, Finger::Unassigned],
+                    vec![Finger::Unassigned],
+                ]
+                .into()
+            ))
+        );
+
+        let ids = vec![vec![0, 1, 2], vec![10, 11, 12], vec![20]];
+        inter.key_ids = Some(ids.clone());
+
+        assert_eq!(inter.pad_layers(Key::Transparent), vec![3, 3, 1]);
+        assert_eq!(inter.key_ids, Some(ids));
+    }
+
+    #[test]
+    fn auto_shift() {
+        let json = r#"{
+            "name": "Auto",
+            "board": "ortho",
+            "auto_shift": true,
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p",
+                    "a s d f g  h j k l ;",
+                    "z x c v b  n m , . /",
+                    "@altgr spc ~  ~ e ~"
+                ],
+                "altgr": [
+                    "* * * * *  * * * * *",
+                    "* * * * *  * * * * *",
+                    "* * * * *  * * * * *",
+                    "* * *  * * *"
+                ]
+            },
+            "fingering": "traditional"
+        }"#;
+
+        let dof = serde_json::from_str::<Dof>(json).expect("couldn't parse auto_shift");
+        let shift = dof.shift_layer();
+
+        assert!(dof.auto_shift());
+        assert_eq!(shift.0[0][0], Key::Char('Q'));
+        assert_eq!(shift.0[2][9], Key::Char('?'));
+        assert_eq!(shift.0[3][0], Key::Transparent);
+        assert_eq!(shift.0[3][1], Key::Transparent);
+
+        let inter = DofIntermediate::from(dof);
+        assert_eq!(inter.auto_shift, Some(true));
+        assert!(!inter.layers.contains_key("shift"));
+
+        let mut inter = inter;
+        inter.layers.insert(
+            "shift".into(),
+            DofIntermediate::generate_shift_layer(inter.main_layer().unwrap()),
+        );
+
+        let err = Dof::try_from(inter).expect_err("shift layer can't be provided");
+        assert!(matches!(err.0.as_ref(), DErr::AutoShiftWithShiftLayer));
+    }
+
+    #[test]
+    fn check_slot_count() {
+        let mut inter = serde_json::from_str::<DofIntermediate>(include_str!(
+            "../example_dofs/minimal_valid.dof"
+        ))
+        .expect("couldn't parse minimal dof");
+
+        assert_eq!(inter.check_slot_count("main"), Ok(None));
+
+        inter
+            .layers
+            .insert("tiny".into(), vec![vec![Key::Char('a'); 10]].into());
+        assert_eq!(inter.check_slot_count("tiny"), Ok(Some((10, 61))));
+
+        inter.board = ParseKeyboard::Named(KeyboardType::Ortho);
+        assert_eq!(inter.check_slot_count("tiny"), Ok(None));
+        assert!(inter.check_slot_count("nope").is_err());
+    }
+
+    #[test]
+    fn from_str_with_template() {
+        let template =
+            serde_json::from_str::<DofIntermediate>(include_str!("../example_dofs/maximal.dof"))
+                .expect("couldn't parse maximal dof");
+
+        let variant = DofIntermediate::from_str_with_template(
+            r#"{
+                "name": "Variant",
+                "layers": {
+                    "altgr": [
+                        "* * * * * *   * * * * * * * bsp",
+                        "tb * * * * *  * * * * * * * *",
+                        "cps * * * * *   * * * * * * ret",
+                        "shft * * * * *   * * * * * shft",
+                        "~ ~ ~ ~      spc      * ~ ~"
+                    ]
+                }
+            }"#,
+            &template,
+        )
+        .expect("couldn't parse variant");
+
+        assert_eq!(variant.name, "Variant");
+        assert_eq!(variant.board, template.board);
+        assert_eq!(variant.fingering, template.fingering);
+        assert_eq!(variant.layers.len(), template.layers.len());
+        assert_eq!(variant.layers["main"], template.layers["main"]);
+        assert_ne!(variant.layers["altgr"], template.layers["altgr"]);
+
+        assert!(DofIntermediate::from_str_with_template("[]", &template).is_err());
+        assert!(DofIntermediate::from_str_with_template(r#"{"board": 1}"#, &template).is_err());
+    }
+
+    #[test]
+    fn layer_fingerings() {
+        let json = r#"{
+            "name": "Per layer",
+            "board": "ortho",
+            "layers": {
+                "main": [
+                    "q w e r t  y u i o p",
+                    "a s d f g  h j k l ;",
+                    "z x c v b  n m , . /",
+                    "~ spc ~  ~ e ~"
+                ],
+                "fn": [
+                    "1 2 3 4 5  6 7 8 9 0",
+                    "* * * * *  * * * * *",
+                    "* * * * *  * * * * *",
+                    "* * *  * * *"
+                ]
+            },
+            "fingering": "traditional",
+            "fingerings": {
+                "fn": [
+                    "LP LP LR LM LI  RI RM RR RP RP",
+                    "LP LR LM LI LI  RI RI RM RR RP",
+                    "LP LR LM LI LI  RI RI RM RR RP",
+                    "LT LT LT  RT RT RT"
+                ]
+            }
+        }"#;
+
+        let mut inter = serde_json::from_str::<DofIntermediate>(json).expect("couldn't parse");
+
+        assert_eq!(
+            inter.fingering_for("main"),
+            Ok(&ParsedFingering::Implicit(NamedFingering::Traditional))
+        );
+        assert!(matches!(
+            inter.fingering_for("fn"),
+            Ok(ParsedFingering::Explicit(_))
+        ));
+        assert!(inter.fingering_for("nope").is_err());
+
+        assert_eq!(inter.finger_for_char("fn", '2'), Ok(Some(Finger::LP)));
+        assert_eq!(
+            inter.explicit_fingering_for("fn").unwrap().inner()[0][1],
+            Finger::LP
+        );
+
+        assert_eq!(inter.keys_for_finger("fn", Finger::LP).unwrap().len(), 4);
+        assert_eq!(inter.keys_for_finger("main", Finger::LP).unwrap().len(), 3);
+        assert_eq!(
+            inter
+                .key_finger_pairs_lossy("fn")
+                .unwrap()
+                .map(|(_, &f)| f)
+                .take(3)
+                .collect::<Vec<_>>(),
+            [Finger::LP, Finger::LP, Finger::LR]
+        );
+        assert_eq!(inter.unfingered_keys("fn"), Ok(vec![]));
+        assert!(inter
+            .render_with_fingering("fn")
+            .unwrap()
+            .starts_with("1  2  3  4  5  6  7  8  9  0\nLP LP LR LM LI RI RM RR RP RP\n"));
+        assert_eq!(inter.assert_one_finger_per_key("fn"), Ok(()));
+
+        let mut ragged = inter.clone();
+        if let Some(ParsedFingering::Explicit(f)) =
+            ragged.fingerings.as_mut().unwrap().get_mut("fn")
+        {
+            f.0[3].pop();
+        }
+        assert_eq!(
+            ragged.assert_one_finger_per_key("fn"),
+            Err(DErr::FingerCountMismatch(3, 5).into())
+        );
+        assert_eq!(ragged.assert_one_finger_per_key("main"), Ok(()));
+
+        let dof = Dof::try_from(inter.clone()).expect("couldn't validate");
+        assert_eq!(dof.fingering_for("fn").inner()[0][1], Finger::LP);
+        assert_eq!(dof.fingering_for("main").inner()[0][1], Finger::LR);
+        inter.fingering = None;
+        assert_eq!(DofIntermediate::from(dof), inter);
+
+        inter.fingerings.as_mut().unwrap().insert(
+            "missing".into(),
+            ParsedFingering::Implicit(NamedFingering::Angle),
+        );
+
+        let err = Dof::try_from(inter).expect_err("layer doesn't exist");
+        assert!(matches!(err.0.as_ref(), DErr::LayerDoesntExist(_)));
+    }
+
     #[test]
     fn lang_fn() {
         let languages = &[Language::new("English", 100)];
@@ -1517,4 +5047,176 @@ mod tests {
 
         println!("{:?}", languages)
     }
+
+    mod round_trip {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn arb_token() -> impl Strategy<Value = String> {
+            prop::collection::vec(
+                any::<char>().prop_filter("whitespace", |c| !c.is_whitespace()),
+                1..8,
+            )
+            .prop_map(|chars| chars.into_iter().collect())
+        }
+
+        fn arb_simple_key() -> impl Strategy<Value = Key> {
+            use SpecialKey::*;
+
+            prop_oneof![
+                Just(Key::Empty),
+                Just(Key::Transparent),
+                any::<char>()
+                    .prop_filter("whitespace", |c| !c.is_whitespace())
+                    .prop_map(Key::Char),
+                arb_token().prop_map(Key::Word),
+                "[ \t\"\\\\a-z]{0,4}[ \t][ \t\"\\\\a-z]{0,4}".prop_map(Key::Word),
+                prop::sample::select(vec![
+                    Esc, Repeat, Space, Tab, Enter, Shift, Caps, Ctrl, Alt, Meta, Menu, Fn,
+                    Backspace, Del, IsoEnter,
+                ])
+                .prop_map(Key::Special),
+                arb_token().prop_map(|name| Key::Layer { name }),
+                arb_token().prop_map(Key::Marker),
+            ]
+        }
+
+        fn arb_key() -> impl Strategy<Value = Key> {
+            // Parentheses in the parts of a tap-hold make it ambiguous where the tap part ends.
+            let tap_hold = arb_simple_key()
+                .prop_filter("parentheses", |k| !k.to_string().contains(['(', ')']))
+                .prop_recursive(2, 8, 2, |part| {
+                    (part.clone(), part).prop_map(|(tap, hold)| Key::TapHold {
+                        tap: Box::new(tap),
+                        hold: Box::new(hold),
+                    })
+                });
+
+            prop_oneof![arb_simple_key(), tap_hold]
+        }
+
+        fn arb_layer() -> impl Strategy<Value = Layer> {
+            (
+                prop::collection::vec(prop::collection::vec(arb_key(), 0..12), 0..5),
+                prop::option::of(any::<String>()),
+                prop::option::of(any::<String>()),
+            )
+                .prop_map(|(keys, color, description)| {
+                    Layer::from(keys).with_metadata(LayerMetadata { color, description })
+                })
+        }
+
+        fn arb_fingering() -> impl Strategy<Value = ParsedFingering> {
+            let row = (
+                prop::collection::vec(
+                    prop::sample::select([&Finger::FINGERS[..], &[Finger::Unassigned]].concat()),
+                    0..12,
+                ),
+                any::<bool>(),
+            );
+
+            prop_oneof![
+                prop::collection::vec(row, 0..5).prop_map(|rows| {
+                    // Rows without an assigned finger are written the same in either notation.
+                    let (fingers, notations) = rows
+                        .into_iter()
+                        .map(|(row, numeric)| {
+                            let notation = match numeric && row.iter().any(|f| !f.is_unassigned()) {
+                                true => RowNotation::Numeric,
+                                false => RowNotation::Letters,
+                            };
+                            (row, notation)
+                        })
+                        .unzip();
+
+                    ParsedFingering::Explicit(Fingering(fingers, notations))
+                }),
+                prop::sample::select(vec![NamedFingering::Traditional, NamedFingering::Angle])
+                    .prop_map(ParsedFingering::Implicit),
+                Just(ParsedFingering::None),
+            ]
+        }
+
+        fn arb_anchor() -> impl Strategy<Value = ParsedAnchor> {
+            prop_oneof![
+                (any::<u8>(), any::<u8>()).prop_map(|(x, y)| Anchor::new(x, y).into()),
+                any::<char>().prop_map(ParsedAnchor::Char),
+            ]
+        }
+
+        fn arb_dof_intermediate() -> impl Strategy<Value = DofIntermediate> {
+            (
+                any::<String>(),
+                prop::option::of("[0-9]{1,2}\\.[0-9]{1,2}\\.[0-9]{1,2}"),
+                prop::option::of(prop::collection::vec(any::<String>(), 0..3)),
+                prop::sample::select(vec![
+                    KeyboardType::Ansi,
+                    KeyboardType::Iso,
+                    KeyboardType::Ortho,
+                    KeyboardType::Colstag,
+                ]),
+                prop::option::of(any::<u32>()),
+                prop::option::of(any::<String>()),
+                prop::collection::btree_map("[a-z]{1,8}", arb_layer(), 0..4),
+                arb_layer(),
+                prop::option::of("[a-z]{1,8}"),
+                prop::option::of(arb_anchor()),
+                prop::option::of(arb_fingering()),
+            )
+                .prop_map(
+                    |(
+                        name,
+                        version,
+                        authors,
+                        board,
+                        year,
+                        description,
+                        mut layers,
+                        main,
+                        base_layer,
+                        anchor,
+                        fingering,
+                    )| {
+                        layers.insert("main".into(), main);
+                        let layers = layers.into_iter().collect();
+
+                        DofIntermediate {
+                            name,
+                            version,
+                            authors,
+                            board: ParseKeyboard::Named(board),
+                            year,
+                            description,
+                            languages: None,
+                            link: None,
+                            tags: None,
+                            corpus: None,
+                            layers,
+                            base_layer,
+                            anchor,
+                            geometry: None,
+                            key_ids: None,
+                            thumbs: None,
+                            thumb_fingering: None,
+                            auto_shift: None,
+                            case_insensitive_layers: None,
+                            fingerings: None,
+                            locked: None,
+                            checksum: None,
+                            fingering,
+                        }
+                    },
+                )
+        }
+
+        proptest! {
+            #[test]
+            fn serialize_parse_equivalence(inter in arb_dof_intermediate()) {
+                let json = serde_json::to_string(&inter).expect("couldn't serialize");
+                let parsed = serde_json::from_str::<DofIntermediate>(&json).expect("couldn't parse");
+
+                prop_assert_eq!(parsed, inter);
+            }
+        }
+    }
 }
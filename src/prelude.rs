@@ -1,9 +1,14 @@
 //! Just exports everything the library offers
 
 pub use crate::{
-    dofinitions::{Finger, Key, KeyboardType, NamedFingering, Shape, SpecialKey},
+    analysis::{Corpus, Effort, FingeringComparison, FingeringStats, RollKind},
+    dofinitions::{
+        Cluster, Finger, FingerNotation, Key, KeyboardType, NamedFingering, Shape, SpecialKey,
+    },
     interaction::{KeyPos, Pos},
-    keyboard::{ParseKeyboard, PhysicalKey, PhysicalKeyboard, RelativeKey, RelativeKeyboard},
+    keyboard::{
+        Geometry, ParseKeyboard, PhysicalKey, PhysicalKeyboard, RelativeKey, RelativeKeyboard,
+    },
     Anchor, DescriptiveKey, Dof, DofError, DofIntermediate, Fingering, Keyboard, Language, Layer,
-    ParsedFingering,
+    LayerMap, LayerMetadata, ParsedAnchor, ParsedFingering,
 };
@@ -0,0 +1,549 @@
+//! Contains functions to render layers of a layout into other formats, like SVG.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    dofinitions::Key,
+    keyboard::{PhysicalKey, PhysicalKeyboard},
+    DofIntermediate, Keyboard, Layer, Result,
+};
+
+/// Size of one key unit in pixels.
+const UNIT: f64 = 60.0;
+/// Space between keys in pixels.
+const GAP: f64 = 4.0;
+
+impl DofIntermediate {
+    /// Render a layer as an SVG image in a minimal flat style, with one rectangle and centered legend
+    /// per key. Keys are positioned and sized using the physical board, and the key the layout is
+    /// anchored on is highlighted.
+    pub fn to_svg(&self, layer: &str) -> Result<String> {
+        self.to_svg_with_legends(layer, &BTreeMap::new())
+    }
+
+    /// Render a layer as an SVG image like [`to_svg()`](crate::DofIntermediate::to_svg), but show the
+    /// text in `legends` on keys that are in it instead of their default legend. The keys themselves are
+    /// left untouched.
+    pub fn to_svg_with_legends(
+        &self,
+        layer: &str,
+        legends: &BTreeMap<Key, String>,
+    ) -> Result<String> {
+        let layer = self.layer_or_err(layer)?;
+        let board = self.physical_board_for(layer)?;
+
+        // The board is cut off at the anchor, so keys are moved to start at the top left of the image.
+        let (min_x, min_y) = board
+            .keys()
+            .map(|k| (k.x(), k.y()))
+            .reduce(|(x1, y1), (x2, y2)| (x1.min(x2), y1.min(y2)))
+            .unwrap_or_default();
+
+        let (width, height) = board.keys().fold((0.0f64, 0.0f64), |(w, h), k| {
+            (
+                w.max(k.x() - min_x + k.width()),
+                h.max(k.y() - min_y + k.height()),
+            )
+        });
+
+        let mut svg = String::new();
+
+        // Writing to a String can't fail.
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">"#,
+            width * UNIT,
+            height * UNIT,
+            width * UNIT,
+            height * UNIT
+        );
+
+        for (row, (key_row, phys_row)) in layer.rows().zip(board.rows()).enumerate() {
+            for (col, (key, phys)) in key_row.iter().zip(phys_row).enumerate() {
+                let fill = match (row, col) {
+                    (0, 0) => "#f5d76e",
+                    _ => "#eeeeee",
+                };
+
+                let _ = writeln!(
+                    svg,
+                    r##"  <rect x="{}" y="{}" width="{}" height="{}" rx="4" fill="{fill}" stroke="#555555"/>"##,
+                    (phys.x() - min_x) * UNIT + GAP / 2.0,
+                    (phys.y() - min_y) * UNIT + GAP / 2.0,
+                    phys.width() * UNIT - GAP,
+                    phys.height() * UNIT - GAP,
+                );
+
+                let _ = writeln!(
+                    svg,
+                    r#"  <text x="{}" y="{}" text-anchor="middle" dominant-baseline="central" font-family="monospace" font-size="20">{}</text>"#,
+                    (phys.x() - min_x + phys.width() / 2.0) * UNIT,
+                    (phys.y() - min_y + phys.height() / 2.0) * UNIT,
+                    escape_xml(&legend(key, legends))
+                );
+            }
+        }
+
+        svg.push_str("</svg>\n");
+
+        Ok(svg)
+    }
+
+    /// Render a layer as plain text, one line per row with keys column-aligned by position. Keys that are
+    /// in `legends` are shown with that text, other keys use their `Display` implementation. The keys
+    /// themselves are left untouched.
+    pub fn render_ascii_with_legends(
+        &self,
+        layer: &str,
+        legends: &BTreeMap<Key, String>,
+    ) -> Result<String> {
+        let layer = self.layer_or_err(layer)?;
+
+        let rows = layer
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|key| match legends.get(key) {
+                        Some(legend) => legend.clone(),
+                        None => key.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut widths = Vec::<usize>::new();
+        for (col, cell) in rows.iter().flat_map(|r| r.iter().enumerate()) {
+            let width = cell.width();
+            match widths.get_mut(col) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+
+        let text = rows
+            .iter()
+            .map(|row| {
+                let line = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, &width)| pad(cell, width))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                format!("{}\n", line.trim_end())
+            })
+            .collect();
+
+        Ok(text)
+    }
+
+    /// Render a layer as plain text with the finger of each key printed underneath it. Keys are
+    /// column-aligned by position, so ragged rows still line up, and rows are separated by an empty line.
+    /// Keys that have no finger assigned to them get nothing printed underneath.
+    pub fn render_with_fingering(&self, layer: &str) -> Result<String> {
+        let fingering = self.unshaped_fingering_for(layer)?;
+        let layer = self.layer_or_err(layer)?;
+
+        let rows = layer
+            .rows()
+            .enumerate()
+            .map(|(i, row)| {
+                let fingers = fingering.inner().get(i).map(Vec::as_slice).unwrap_or(&[]);
+
+                row.iter()
+                    .enumerate()
+                    .map(|(j, key)| {
+                        let finger = fingers.get(j).map(ToString::to_string);
+                        (key.to_string(), finger.unwrap_or_default())
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut widths = Vec::<usize>::new();
+        for (col, (key, finger)) in rows.iter().flat_map(|r| r.iter().enumerate()) {
+            let width = key.width().max(finger.width());
+            match widths.get_mut(col) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+
+        let line = |cells: &mut dyn Iterator<Item = &String>| {
+            cells
+                .zip(&widths)
+                .map(|(cell, &width)| pad(cell, width))
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim_end()
+                .to_owned()
+        };
+
+        let text = rows
+            .iter()
+            .map(|row| {
+                let keys = line(&mut row.iter().map(|(k, _)| k));
+                let fingers = line(&mut row.iter().map(|(_, f)| f));
+                format!("{keys}\n{fingers}\n")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(text)
+    }
+
+    /// Render a layer as a GitHub-flavored Markdown table with one cell per key. The header row is left
+    /// empty, as every row of the layer is a row of keys. Shorter rows are padded with empty cells so the
+    /// table stays rectangular, and pipe characters in legends are escaped.
+    pub fn to_markdown(&self, layer: &str) -> Result<String> {
+        let layer = self.layer_or_err(layer)?;
+        let width = layer.rows().map(Vec::len).max().unwrap_or_default();
+
+        let table_row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+        let mut out = table_row(vec![String::new(); width]);
+        out.push_str(&table_row(vec!["---".to_string(); width]));
+
+        for row in layer.rows() {
+            let mut cells = row
+                .iter()
+                .map(|k| k.to_string().replace('|', "\\|"))
+                .collect::<Vec<_>>();
+            cells.resize(width, String::new());
+
+            out.push_str(&table_row(cells));
+        }
+
+        Ok(out)
+    }
+
+    /// Render the differences between this layout and `other` as human-readable text. Every layer that
+    /// changed is printed with a `name:` header followed by its rows, where changed keys are written as
+    /// `[old>new]` inline. Layers that only exist in one of the two layouts get a `+ name` or `- name`
+    /// header line instead. When the shapes of a layer differ, positions missing on one side are written
+    /// as `[old>]` or `[>new]`. Returns an empty string if the layouts have identical layers.
+    pub fn render_diff(&self, other: &DofIntermediate) -> String {
+        let mut out = String::new();
+
+        for (name, layer) in &self.layers {
+            let Some(other_layer) = other.layers.get(name) else {
+                out.push_str(&format!("- {name}\n"));
+                continue;
+            };
+
+            if layer.0 == other_layer.0 {
+                continue;
+            }
+
+            out.push_str(&format!("{name}:\n"));
+
+            for y in 0..layer.0.len().max(other_layer.0.len()) {
+                let old = layer.0.get(y).map(Vec::as_slice).unwrap_or_default();
+                let new = other_layer.0.get(y).map(Vec::as_slice).unwrap_or_default();
+
+                let row = (0..old.len().max(new.len()))
+                    .map(|x| match (old.get(x), new.get(x)) {
+                        (Some(a), Some(b)) if a == b => a.to_string(),
+                        // A key missing on one side is written as nothing, like `[a>]`.
+                        (a, b) => format!(
+                            "[{}>{}]",
+                            a.map(ToString::to_string).unwrap_or_default(),
+                            b.map(ToString::to_string).unwrap_or_default()
+                        ),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                out.push_str(&row);
+                out.push('\n');
+            }
+        }
+
+        for name in other.layers.keys() {
+            if !self.layers.contains_key(name) {
+                out.push_str(&format!("+ {name}\n"));
+            }
+        }
+
+        out
+    }
+
+    /// Export every key of the layout as CSV with the columns `layer,row,col,key,finger`, starting with a
+    /// header line. Layers are written in the order of the [`LayerMap`](crate::LayerMap), and keys in
+    /// row-major order. Keys are written like they would be in a .dof file, and the finger comes from the
+    /// fingering of the layer. It is left blank if the layer has no fingering or the key isn't assigned a
+    /// finger. Fields are quoted following RFC 4180 where needed.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("layer,row,col,key,finger\n");
+
+        for (name, layer) in &self.layers {
+            let fingering = self.explicit_fingering_for(name).ok();
+
+            for (row, keys) in layer.rows().enumerate() {
+                for (col, key) in keys.iter().enumerate() {
+                    let finger = fingering
+                        .as_ref()
+                        .and_then(|f| f.inner().get(row)?.get(col))
+                        .filter(|f| !f.is_unassigned())
+                        .map(ToString::to_string)
+                        .unwrap_or_default();
+
+                    // Writing to a `String` can't fail.
+                    writeln!(
+                        out,
+                        "{},{row},{col},{},{finger}",
+                        escape_csv(name),
+                        escape_csv(&key.to_string())
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
+        out
+    }
+
+    /// Get the physical board of the layout, cut to the shape of the given layer with the anchor.
+    fn physical_board_for(&self, layer: &Layer) -> Result<PhysicalKeyboard> {
+        let anchor = self.resolved_anchor()?;
+
+        let board = PhysicalKeyboard::try_from(self.board.clone())?
+            .resized(anchor, layer.shape())?
+            .into_iter()
+            .map(|r| r.into_iter().map(PhysicalKey::normalized).collect())
+            .collect::<Vec<_>>();
+
+        Ok(board.into())
+    }
+}
+
+/// Text shown on a key. Keys in `legends` show the text they map to, empty keys show nothing and other
+/// keys use their `Display` implementation.
+fn legend(key: &Key, legends: &BTreeMap<Key, String>) -> String {
+    match (legends.get(key), key) {
+        (Some(legend), _) => legend.clone(),
+        (None, Key::Empty) => String::new(),
+        (None, k) => k.to_string(),
+    }
+}
+
+/// Pad a cell with spaces until it takes up `width` columns in a terminal. Unlike `format!("{:<width$}")`
+/// this accounts for characters that are wider or narrower than a single column, like `中`.
+fn pad(cell: &str, width: usize) -> String {
+    format!("{cell}{}", " ".repeat(width.saturating_sub(cell.width())))
+}
+
+fn escape_xml(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '&' => "&amp;".into(),
+            '<' => "&lt;".into(),
+            '>' => "&gt;".into(),
+            '"' => "&quot;".into(),
+            '\'' => "&apos;".into(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// Quote a CSV field if it contains a comma, quote or line break, doubling any quotes inside of it.
+fn escape_csv(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        dofinitions::{Finger, SpecialKey},
+        ParsedFingering,
+    };
+
+    static MINIMAL: &str = include_str!("../example_dofs/minimal_valid.dof");
+
+    #[test]
+    fn to_svg() {
+        let inter = serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let svg = inter.to_svg("main").expect("couldn't render svg");
+
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 31);
+        assert_eq!(svg.matches("<text").count(), 31);
+        assert_eq!(svg.matches("#f5d76e").count(), 1);
+        assert!(svg.contains(">&apos;</text>"));
+        assert!(svg.contains(r#"<rect x="2" y="2" width="56" height="56""#));
+        assert!(!svg.contains(r#"x="-"#));
+
+        assert!(inter.to_svg("altgr").is_err());
+    }
+
+    #[test]
+    fn legends() {
+        use crate::dofinitions::SpecialKey;
+
+        let aptmak = include_str!("../example_dofs/aptmak.dof");
+        let inter = serde_json::from_str::<DofIntermediate>(aptmak).expect("couldn't parse json");
+
+        let legends = BTreeMap::from_iter([
+            (Key::Special(SpecialKey::Space), "␣".to_string()),
+            (Key::Char('e'), "E".to_string()),
+        ]);
+
+        let text = inter
+            .render_ascii_with_legends("main", &legends)
+            .expect("couldn't render ascii");
+        let lines = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "v w f p b j l u y '");
+        assert_eq!(lines[3], "~ ␣ ~ ~ E ~");
+        assert_eq!(
+            inter.layers["main"].0[3][1],
+            Key::Special(SpecialKey::Space)
+        );
+
+        let plain = inter
+            .render_ascii_with_legends("main", &BTreeMap::new())
+            .expect("couldn't render ascii");
+
+        assert_eq!(plain.lines().nth(3), Some("~ spc ~ ~ e ~"));
+
+        let wide = BTreeMap::from_iter([(Key::Char('v'), "中".to_string())]);
+        let text = inter
+            .render_ascii_with_legends("main", &wide)
+            .expect("couldn't render ascii");
+        let lines = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "中 w   f p b j l u y '");
+        assert_eq!(lines[1], "r  s   t h k x n a i o");
+
+        let svg = inter
+            .to_svg_with_legends("main", &legends)
+            .expect("couldn't render svg");
+
+        assert!(svg.contains(">␣</text>"));
+        assert!(svg.contains(">E</text>"));
+        assert!(!svg.contains(">e</text>"));
+
+        assert!(inter.render_ascii_with_legends("altgr", &legends).is_err());
+    }
+
+    #[test]
+    fn render_with_fingering() {
+        use crate::{dofinitions::Finger::*, Fingering, ParsedFingering};
+
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+
+        let text = inter
+            .render_with_fingering("main")
+            .expect("couldn't render fingering");
+        let lines = text.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "q  w  e  r  t  y  u  i  o  p");
+        assert_eq!(lines[1], "LP LR LM LI LI RI RI RM RR RP");
+        assert_eq!(lines[2], "");
+        assert_eq!(lines[4], "LP LR LM LI LI RI RI RM RR RP RP");
+        assert_eq!(lines[6], "z  x  c  v  b  n  m  ,  .  /");
+        assert_eq!(lines[7], "LR LM LI LI LI RI RI RM RR RP");
+
+        inter.fingering = Some(ParsedFingering::Explicit(Fingering::from(vec![vec![
+            LP, LR,
+        ]])));
+
+        let text = inter
+            .render_with_fingering("main")
+            .expect("couldn't render fingering");
+
+        assert!(text.starts_with("q  w  e r t y u i o p\nLP LR\n\n"));
+        assert!(inter.render_with_fingering("altgr").is_err());
+    }
+
+    #[test]
+    fn to_markdown() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        inter.layers.insert(
+            "small".into(),
+            vec![
+                vec![Key::Char('a'), Key::Char('|')],
+                vec![Key::Special(SpecialKey::Space)],
+            ]
+            .into(),
+        );
+
+        assert_eq!(
+            inter.to_markdown("small"),
+            Ok("|  |  |\n| --- | --- |\n| a | \\| |\n| spc |  |\n".to_string())
+        );
+
+        let main = inter.to_markdown("main").unwrap();
+        assert_eq!(main.lines().count(), 5);
+        assert!(main.ends_with("| z | x | c | v | b | n | m | , | . | / |  |\n"));
+
+        assert!(inter.to_markdown("nope").is_err());
+    }
+
+    #[test]
+    fn render_diff() {
+        let mut old =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        old.layers
+            .insert("shift".into(), vec![vec![Key::Char('Q')]].into());
+
+        assert_eq!(old.render_diff(&old), "");
+
+        let mut new = old.clone();
+        let main = new.layers.get_mut("main").unwrap();
+        main.0[0][0] = Key::Char('x');
+        main.0[2].pop();
+        new.layers
+            .insert("extra".into(), vec![vec![Key::Transparent]].into());
+        new.layers.remove("shift");
+
+        let diff = old.render_diff(&new);
+        let lines = diff.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "main:");
+        assert!(lines[1].starts_with("[q>x] w e r t"));
+        assert!(lines[3].ends_with(". [/>]"));
+        assert_eq!(&lines[4..], ["- shift", "+ extra"]);
+
+        let reversed = new.render_diff(&old);
+
+        assert!(reversed.lines().any(|l| l.ends_with(". [>/]")));
+    }
+
+    #[test]
+    fn to_csv() {
+        let mut inter =
+            serde_json::from_str::<DofIntermediate>(MINIMAL).expect("couldn't parse json");
+        inter.layers.insert(
+            "small".into(),
+            vec![vec![Key::Char(','), Key::Word("a\"b".into())]].into(),
+        );
+        inter.fingerings = Some(BTreeMap::from([(
+            "small".to_string(),
+            ParsedFingering::Explicit(vec![vec![Finger::LI, Finger::Unassigned]].into()),
+        )]));
+
+        let csv = inter.to_csv();
+        let lines = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "layer,row,col,key,finger");
+        assert_eq!(lines[1], "main,0,0,q,LP");
+        assert_eq!(lines[11], "main,1,0,a,LP");
+        assert_eq!(lines.len(), 1 + 31 + 2);
+        assert_eq!(lines[32], "small,0,0,\",\",LI");
+        assert_eq!(lines[33], "small,0,1,\"a\"\"b\",");
+    }
+}
@@ -0,0 +1,202 @@
+//! Contains a [JSON Schema](https://json-schema.org) generator for the .dof format, which can be used by
+//! editors to validate and autocomplete .dof files. Requires the `schema` feature.
+
+use std::borrow::Cow;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::{
+    keyboard::{Geometry, ParseKeyboard},
+    DofIntermediate, Fingering, Layer, LayerMap, ParsedFingering,
+};
+
+/// Generate a JSON Schema describing the structure of a .dof file, based on
+/// [`DofIntermediate`](crate::DofIntermediate). Layers, fingerings and relative boards are described
+/// as arrays of row strings, like they appear in the file itself.
+pub fn json_schema() -> serde_json::Value {
+    schemars::schema_for!(DofIntermediate).to_value()
+}
+
+impl JsonSchema for Layer {
+    fn schema_name() -> Cow<'static, str> {
+        "Layer".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                {
+                    "description": "Rows of keys, where each key is separated by whitespace.",
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                {
+                    "description": "Rows of keys in `keys`, with optional metadata about the layer.",
+                    "type": "object",
+                    "properties": {
+                        "keys": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "color": { "type": "string" },
+                        "description": { "type": "string" }
+                    },
+                    "required": ["keys"]
+                }
+            ]
+        })
+    }
+}
+
+impl JsonSchema for LayerMap {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "LayerMap".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        std::collections::BTreeMap::<String, Layer>::json_schema(generator)
+    }
+}
+
+impl JsonSchema for Fingering {
+    fn schema_name() -> Cow<'static, str> {
+        "Fingering".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Rows of fingers, where each finger is either `LP` to `RP` or `0` to `9`, \
+                or `-` or `x` for no finger, separated by whitespace.",
+            "type": "array",
+            "items": { "type": "string" }
+        })
+    }
+}
+
+impl JsonSchema for ParsedFingering {
+    fn schema_name() -> Cow<'static, str> {
+        "ParsedFingering".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                generator.subschema_for::<Fingering>(),
+                {
+                    "description": "A named fingering.",
+                    "enum": ["traditional", "standard", "angle", "wide", "angle-wide"]
+                },
+                {
+                    "description": "A custom named fingering, or no fingering if empty.",
+                    "type": "string"
+                },
+                {
+                    "description": "No fingering.",
+                    "type": "null"
+                }
+            ]
+        })
+    }
+}
+
+impl JsonSchema for ParseKeyboard {
+    fn schema_name() -> Cow<'static, str> {
+        "ParseKeyboard".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "anyOf": [
+                {
+                    "description": "A named keyboard type.",
+                    "enum": ["ansi", "iso", "ortho", "colstag"]
+                },
+                {
+                    "description": "A custom keyboard type.",
+                    "type": "string"
+                },
+                {
+                    "description": "Rows of relative keys, like `k 2k 1.5k`, separated by whitespace.",
+                    "type": "array",
+                    "items": { "type": "string" }
+                },
+                {
+                    "description": "Rows of physical keys, each formatted as `x y [width] [height]`.",
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                }
+            ]
+        })
+    }
+}
+
+impl JsonSchema for Geometry {
+    fn schema_name() -> Cow<'static, str> {
+        "Geometry".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Explicit geometry of the board.",
+            "type": "object",
+            "properties": {
+                "row_offsets": {
+                    "description": "x-offset of each row.",
+                    "type": "array",
+                    "items": { "type": "number" }
+                },
+                "keys": {
+                    "description": "Rows of key centers, each formatted as `x y`.",
+                    "type": "array",
+                    "items": {
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_structure() {
+        let schema = json_schema();
+
+        let properties = &schema["properties"];
+        assert_eq!(properties["name"]["type"], "string");
+        assert_eq!(
+            properties["layers"]["additionalProperties"]["$ref"],
+            "#/$defs/Layer"
+        );
+        assert_eq!(
+            schema["$defs"]["Layer"]["anyOf"][0]["items"]["type"],
+            "string"
+        );
+        assert_eq!(schema["$defs"]["Layer"]["anyOf"][1]["required"][0], "keys");
+        assert_eq!(
+            schema["$defs"]["ParseKeyboard"]["anyOf"][0]["enum"],
+            serde_json::json!(["ansi", "iso", "ortho", "colstag"])
+        );
+        assert_eq!(
+            schema["$defs"]["ParsedFingering"]["anyOf"][1]["enum"],
+            serde_json::json!(["traditional", "standard", "angle", "wide", "angle-wide"])
+        );
+
+        let required = schema["required"].as_array().expect("no required fields");
+        assert!(required.contains(&"name".into()));
+        assert!(required.contains(&"board".into()));
+        assert!(required.contains(&"layers".into()));
+        assert!(!required.contains(&"fingering".into()));
+    }
+}